@@ -1,9 +1,26 @@
 //! Handling the message having attachments or stickers
 
+#[cfg(feature = "upload")]
+use std::{
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+#[cfg(feature = "upload")]
+use futures_util::{stream, StreamExt, TryStreamExt};
 #[cfg(feature = "reqwest")]
 use reqwest::Client;
-use twilight_model::channel::message::sticker::{MessageSticker, StickerFormatType};
-use twilight_validate::message::MESSAGE_CONTENT_LENGTH_MAX;
+use twilight_model::channel::message::{
+    embed::EmbedImage,
+    sticker::{MessageSticker, StickerFormatType},
+    Embed,
+};
+#[cfg(feature = "upload")]
+use twilight_model::guild::PremiumTier;
+use twilight_validate::message::{EMBED_COUNT_LIMIT, MESSAGE_CONTENT_LENGTH_MAX};
 
 use crate::{error::Error, MessageSource};
 
@@ -14,9 +31,213 @@ pub struct Info<'a> {
     pub stickers: &'a [MessageSticker],
     /// Attachments in the message
     pub attachments: &'a [twilight_model::channel::Attachment],
+    /// Refreshed links of the attachments in the same order
+    ///
+    /// Set in [`MessageSource::refresh_attachment_urls`]
+    pub refreshed_urls: Option<Vec<String>>,
     /// Attachments to re-upload
     #[cfg(feature = "upload")]
     pub attachments_upload: Vec<twilight_model::http::attachment::Attachment>,
+    /// Attachments to download in [`MessageSource::create`]
+    ///
+    /// Set instead of [`Info::attachments_upload`] if
+    /// [`DownloadOptions::deferred`] is set
+    #[cfg(feature = "upload")]
+    pub attachments_deferred: Vec<&'a twilight_model::channel::Attachment>,
+    /// Attachments that were linked instead of being re-uploaded
+    ///
+    /// Set in [`MessageSource::handle_attachment_upload_or_link`]
+    #[cfg(feature = "upload")]
+    pub attachments_link: Vec<&'a twilight_model::channel::Attachment>,
+    /// Combined size limit in bytes of the attachments to re-upload
+    ///
+    /// If `None`, it's resolved from the premium tier of the guild the message
+    /// is cloned to, set this if Discord's limits change or if the premium tier
+    /// is known to be wrong
+    #[cfg(feature = "upload")]
+    pub upload_size_limit: Option<u64>,
+}
+
+/// Future returned from [`AttachmentFetcher::fetch`]
+#[cfg(feature = "upload")]
+pub type FetchFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<u8>, Error>> + Send + 'a>>;
+
+/// Fetches the files of attachments and stickers to re-upload
+///
+/// Implement this to use your own HTTP client, a cache or a local file store,
+/// or to stub downloads in tests, [`ReqwestFetcher`] is used by default
+#[cfg(feature = "upload")]
+pub trait AttachmentFetcher: Debug + Send + Sync {
+    /// Return the file at the link
+    ///
+    /// This is used for stickers, and for attachments unless
+    /// [`AttachmentFetcher::fetch`] is implemented
+    ///
+    /// # Errors
+    ///
+    /// Return [`Error::Fetch`] with your own error if fetching the file
+    /// fails
+    fn fetch_url<'a>(&'a self, url: &'a str) -> FetchFuture<'a>;
+
+    /// Return the file of the attachment
+    ///
+    /// Fetches the attachment's link by default
+    ///
+    /// # Errors
+    ///
+    /// Return [`Error::Fetch`] with your own error if fetching the file
+    /// fails
+    fn fetch<'a>(&'a self, attachment: &'a twilight_model::channel::Attachment) -> FetchFuture<'a> {
+        self.fetch_url(&attachment.url)
+    }
+}
+
+/// Fetcher that downloads attachments from Discord using reqwest
+#[cfg(feature = "upload")]
+#[derive(Clone, Debug)]
+pub struct ReqwestFetcher {
+    /// Client to download the attachments with
+    ///
+    /// Set this to your own client to reuse it, since creating a client is
+    /// expensive
+    pub client: Client,
+    /// Time limit for downloading each attachment
+    ///
+    /// `None` if there's no limit
+    pub timeout: Option<Duration>,
+}
+
+#[cfg(feature = "upload")]
+impl Default for ReqwestFetcher {
+    fn default() -> Self {
+        Self {
+            client: Client::new(),
+            timeout: Some(Duration::from_secs(30)),
+        }
+    }
+}
+
+#[cfg(feature = "upload")]
+impl AttachmentFetcher for ReqwestFetcher {
+    /// # Errors
+    ///
+    /// Returns [`Error::Reqwest`] if downloading the file fails or times out
+    fn fetch_url<'a>(&'a self, url: &'a str) -> FetchFuture<'a> {
+        Box::pin(async move {
+            let mut request = self.client.get(url);
+            if let Some(timeout) = self.timeout {
+                request = request.timeout(timeout);
+            }
+
+            Ok(request.send().await?.bytes().await?.to_vec())
+        })
+    }
+}
+
+/// Options for downloading the attachments to re-upload
+///
+/// Set in [`MessageSource::download_options`]
+#[cfg(feature = "upload")]
+#[derive(Clone, Debug)]
+pub struct DownloadOptions {
+    /// Fetcher to download the attachments with
+    ///
+    /// If `None`, a default [`ReqwestFetcher`] is used, it's created on the
+    /// first download and shared by all message sources
+    pub fetcher: Option<Arc<dyn AttachmentFetcher>>,
+    /// Maximum number of attachments to download at the same time
+    ///
+    /// Values below 1 are treated as 1
+    pub concurrency: usize,
+    /// Whether to download the attachments in [`MessageSource::create`]
+    /// instead of when handling them
+    ///
+    /// This means the files are only kept in memory while the message is
+    /// being created, which is useful when holding many message sources, such
+    /// as the ones returned from [`MessageSource::later_messages`]
+    pub deferred: bool,
+}
+
+#[cfg(feature = "upload")]
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            fetcher: None,
+            concurrency: 4,
+            deferred: false,
+        }
+    }
+}
+
+#[cfg(feature = "upload")]
+impl DownloadOptions {
+    /// Return the set fetcher or the shared default fetcher
+    fn fetcher(&self) -> &dyn AttachmentFetcher {
+        static DEFAULT_FETCHER: OnceLock<ReqwestFetcher> = OnceLock::new();
+
+        self.fetcher
+            .as_deref()
+            .unwrap_or_else(|| DEFAULT_FETCHER.get_or_init(ReqwestFetcher::default))
+    }
+}
+
+/// Memory used by the attachments to re-upload
+///
+/// Returned from [`MessageSource::attachment_memory_usage`]
+#[cfg(feature = "upload")]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MemoryUsage {
+    /// Bytes of the downloaded files held in memory
+    pub buffered: u64,
+    /// Bytes of the files that will be downloaded in [`MessageSource::create`]
+    ///
+    /// See [`DownloadOptions::deferred`]
+    pub deferred: u64,
+}
+
+/// How to handle Lottie stickers in [`MessageSource::handle_sticker_upload`]
+///
+/// Lottie stickers are animations described in JSON, they can't be uploaded
+/// as images as they are
+#[cfg(feature = "upload")]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LottieSticker {
+    /// Return [`Error::StickerLinkInvalid`]
+    #[default]
+    Error,
+    /// Upload a static placeholder image named after the sticker instead
+    ///
+    /// The image is the same generic sticker icon for every sticker, so a
+    /// `*Sticker: name*` line is also appended to the content for each of
+    /// them
+    Placeholder,
+    /// Render the animation to a GIF and upload it
+    ///
+    /// Only the parts of Lottie that stickers commonly use are supported,
+    /// unsupported parts of the animation are left out
+    ///
+    /// Rendering takes CPU time, so it's done in Tokio's blocking thread pool,
+    /// which requires [`MessageSource::handle_sticker_upload`] to be called in
+    /// a Tokio runtime
+    #[cfg(feature = "lottie")]
+    Render,
+}
+
+/// The order to pick attachments to re-upload in when they don't all fit the
+/// upload limit
+///
+/// Used in [`MessageSource::handle_attachment_upload_or_link`]
+#[cfg(feature = "upload")]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum UploadPriority {
+    /// Pick attachments in the order they're in the message
+    #[default]
+    Order,
+    /// Pick images first, then the rest in the order they're in the message
+    ImagesFirst,
+    /// Pick the smallest attachments first, re-uploading as many attachments
+    /// as possible
+    SmallestFirst,
 }
 
 impl MessageSource<'_> {
@@ -24,21 +245,29 @@ impl MessageSource<'_> {
     ///
     /// If the attachment is an image, it's embedded in the client
     ///
+    /// Links to spoiler attachments are put in spoiler markers and the alt
+    /// text of the attachments is added after their links
+    ///
     /// # Warnings
     ///
-    /// The link will die after the source message is deleted
+    /// The link will die after the source message is deleted, it also expires
+    /// after some time, see [`MessageSource::refresh_attachment_urls`]
     ///
     /// # Errors
     ///
     /// Returns [`Error::ContentInvalid`] if the message content becomes
-    /// too long after adding the links
+    /// too long after adding the links and
+    /// [`Options::split_content`](crate::constructor::Options::split_content)
+    /// isn't set
     pub fn handle_attachment_link(mut self) -> Result<Self, Error> {
-        self.append_urls(
-            self.attachment_sticker_info
-                .attachments
-                .iter()
-                .map(|attachment| attachment.url.as_str()),
-        )?;
+        let links = self
+            .attachment_sticker_info
+            .attachments
+            .iter()
+            .map(|attachment| self.attachment_link(attachment))
+            .collect::<Vec<_>>();
+
+        self.append_urls(links.iter().map(String::as_str))?;
 
         Ok(self)
     }
@@ -55,45 +284,183 @@ impl MessageSource<'_> {
     /// [`StickerFormatType::Unknown`]
     ///
     /// Returns [`Error::ContentInvalid`] if the message content becomes
-    /// too long after adding the links
+    /// too long after adding the links and
+    /// [`Options::split_content`](crate::constructor::Options::split_content)
+    /// isn't set
     pub fn handle_sticker_link(mut self) -> Result<Self, Error> {
-        let mut sticker_urls = vec![];
-        for sticker in self.attachment_sticker_info.stickers {
-            sticker_urls.push(format!(
-                "https://cdn.discordapp.com/stickers/{}.{}",
-                sticker.id,
-                match sticker.format_type {
-                    StickerFormatType::Gif => "gif",
-                    StickerFormatType::Png | StickerFormatType::Apng => "png",
-                    _ => return Err(Error::StickerLinkInvalid),
-                }
-            ));
-        }
+        let sticker_urls = self
+            .attachment_sticker_info
+            .stickers
+            .iter()
+            .map(sticker_url)
+            .collect::<Result<Vec<_>, _>>()?;
 
         self.append_urls(sticker_urls.iter().map(String::as_str))?;
 
         Ok(self)
     }
 
+    /// Add embeds with links to the attachments
+    ///
+    /// Images are put in their own embeds so that they're shown in the client,
+    /// other files are put in a single embed listing them, this is useful when
+    /// the content is too long to add links to it
+    ///
+    /// If there's no room for an embed for each image, the rest of the
+    /// images are listed with the other files
+    ///
+    /// Spoiler images are listed with the other files in spoiler markers,
+    /// since they'd be revealed otherwise
+    ///
+    /// # Warnings
+    ///
+    /// The links will die after the source message is deleted, they also
+    /// expire after some time, see [`MessageSource::refresh_attachment_urls`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MessageValidation`] if the embeds are invalid after
+    /// adding the links, for example if the message already has
+    /// [`EMBED_COUNT_LIMIT`] embeds
+    pub fn handle_attachment_embed(mut self) -> Result<Self, Error> {
+        let (images, files): (Vec<_>, Vec<_>) = self
+            .attachment_sticker_info
+            .attachments
+            .iter()
+            .partition(|attachment| {
+                attachment
+                    .content_type
+                    .as_ref()
+                    .is_some_and(|content_type| content_type.starts_with("image/"))
+                    && !is_spoiler(attachment)
+            });
+
+        let image_urls = images
+            .into_iter()
+            .map(|attachment| {
+                (
+                    attachment.filename.as_str(),
+                    self.attachment_url(attachment),
+                    attachment.description.as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let file_urls = files
+            .into_iter()
+            .map(|attachment| {
+                (
+                    attachment.filename.as_str(),
+                    self.attachment_url(attachment),
+                    attachment.description.as_deref(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        self.append_url_embeds(
+            "Attachments",
+            image_urls
+                .iter()
+                .map(|(filename, url, description)| (*filename, url.as_str(), *description))
+                .collect(),
+            file_urls
+                .iter()
+                .map(|(filename, url, description)| (*filename, url.as_str(), *description))
+                .collect(),
+        )?;
+
+        Ok(self)
+    }
+
+    /// Add embeds with the stickers' images
+    ///
+    /// Each sticker is put in its own embed, this is useful when the content
+    /// is too long to add links to it, [`StickerFormatType::Apng`] stickers
+    /// aren't animated
+    ///
+    /// If there's no room for an embed for each sticker, the rest of the
+    /// stickers are listed in a single embed
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::StickerLinkInvalid`] if a sticker's
+    /// [`StickerFormatType`] is [`StickerFormatType::Lottie`] or
+    /// [`StickerFormatType::Unknown`]
+    ///
+    /// Returns [`Error::MessageValidation`] if the embeds are invalid after
+    /// adding the stickers, for example if the message already has
+    /// [`EMBED_COUNT_LIMIT`] embeds
+    pub fn handle_sticker_embed(mut self) -> Result<Self, Error> {
+        let stickers = self
+            .attachment_sticker_info
+            .stickers
+            .iter()
+            .map(|sticker| Ok((sticker.name.as_str(), sticker_url(sticker)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        self.append_url_embeds(
+            "Stickers",
+            stickers
+                .iter()
+                .map(|(name, url)| (*name, url.as_str(), None))
+                .collect(),
+            vec![],
+        )?;
+
+        Ok(self)
+    }
+
+    /// Return the link to the attachment, refreshed if
+    /// [`MessageSource::refresh_attachment_urls`] was called
+    fn attachment_url(&self, attachment: &twilight_model::channel::Attachment) -> String {
+        self.attachment_sticker_info
+            .attachments
+            .iter()
+            .position(|other_attachment| other_attachment.id == attachment.id)
+            .and_then(|index| {
+                self.attachment_sticker_info
+                    .refreshed_urls
+                    .as_ref()?
+                    .get(index)
+            })
+            .unwrap_or(&attachment.url)
+            .clone()
+    }
+
+    /// Return the line to append to the content for the attachment
+    fn attachment_link(&self, attachment: &twilight_model::channel::Attachment) -> String {
+        let url = self.attachment_url(attachment);
+        let link = match &attachment.description {
+            Some(description) => format!("{url} ({description})"),
+            None => url,
+        };
+
+        if is_spoiler(attachment) {
+            format!("||{link}||")
+        } else {
+            link
+        }
+    }
+
     #[allow(single_use_lifetimes)]
     fn append_urls<'a>(
         &mut self,
         urls: impl Iterator<Item = &'a str> + Clone,
     ) -> Result<(), Error> {
-        if self
-            .content
-            .chars()
-            .count()
-            .saturating_add(
-                urls.clone()
-                    // add 1 for newlines
-                    .map(|url| url.chars().count().saturating_add(1))
-                    .reduce(usize::saturating_add)
-                    .unwrap_or(0),
-            )
-            // add 1 for empty line between
-            .saturating_add(1)
-            > MESSAGE_CONTENT_LENGTH_MAX
+        if !self.options.split_content
+            && self
+                .content
+                .chars()
+                .count()
+                .saturating_add(
+                    urls.clone()
+                        // add 1 for newlines
+                        .map(|url| url.chars().count().saturating_add(1))
+                        .reduce(usize::saturating_add)
+                        .unwrap_or(0),
+                )
+                // add 1 for empty line between
+                .saturating_add(1)
+                > MESSAGE_CONTENT_LENGTH_MAX
         {
             return Err(Error::ContentInvalid);
         }
@@ -106,26 +473,196 @@ impl MessageSource<'_> {
 
         Ok(())
     }
+
+    /// Add an embed for each image and an embed listing the other links
+    ///
+    /// Items are names, URLs and descriptions, descriptions are only shown in
+    /// the list
+    fn append_url_embeds<'a>(
+        &mut self,
+        list_title: &str,
+        mut images: Vec<(&'a str, &'a str, Option<&'a str>)>,
+        mut links: Vec<(&'a str, &'a str, Option<&'a str>)>,
+    ) -> Result<(), Error> {
+        let embed_count = self.embeds.len();
+        let embed_slots = EMBED_COUNT_LIMIT.saturating_sub(embed_count);
+
+        if images.len() > embed_slots {
+            // leave a slot for the list
+            links.extend(images.drain(embed_slots.saturating_sub(1)..));
+        } else if !links.is_empty() && images.len() == embed_slots {
+            links.extend(images.pop());
+        }
+
+        for (_, url, _) in images {
+            self.embeds.push(Embed {
+                image: Some(EmbedImage {
+                    height: None,
+                    proxy_url: None,
+                    url: url.to_owned(),
+                    width: None,
+                }),
+                ..empty_embed()
+            });
+        }
+
+        if !links.is_empty() {
+            self.embeds.push(Embed {
+                title: Some(list_title.to_owned()),
+                description: Some(
+                    links
+                        .iter()
+                        .map(|(name, url, description)| {
+                            let link = description.map_or_else(
+                                || format!("[{name}]({url})"),
+                                |alt_text| format!("[{name}]({url}) ({alt_text})"),
+                            );
+
+                            if name.starts_with(SPOILER_PREFIX) {
+                                format!("||{link}||")
+                            } else {
+                                link
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                ),
+                ..empty_embed()
+            });
+        }
+
+        // can't create the error manually
+        if let Err(err) = twilight_validate::message::embeds(&self.embeds) {
+            self.embeds.truncate(embed_count);
+            return Err(err.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Return the link without the query, which has the signature of attachment
+/// links
+#[cfg(feature = "upload")]
+fn url_path(url: &str) -> &str {
+    url.split_once('?').map_or(url, |(path, _)| path)
+}
+
+/// Image uploaded in place of Lottie stickers, see
+/// [`LottieSticker::Placeholder`]
+#[cfg(feature = "upload")]
+const STICKER_PLACEHOLDER: &[u8] = include_bytes!("../assets/sticker_placeholder.png");
+
+/// Return the sticker's name usable as a file name, without the extension
+#[cfg(feature = "upload")]
+fn sticker_file_name(sticker: &MessageSticker) -> String {
+    sticker
+        .name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Return the line added to the content for stickers uploaded as
+/// [`LottieSticker::Placeholder`], with the name's markdown escaped
+#[cfg(feature = "upload")]
+fn sticker_name_line(sticker: &MessageSticker) -> String {
+    let name = sticker
+        .name
+        .chars()
+        .flat_map(|c| {
+            let escape = matches!(c, '\\' | '*' | '_' | '~' | '|' | '`').then_some('\\');
+            escape.into_iter().chain([c])
+        })
+        .collect::<String>();
+
+    format!("*Sticker: {name}*")
+}
+
+/// Prefix of the file names of spoiler attachments
+const SPOILER_PREFIX: &str = "SPOILER_";
+
+fn is_spoiler(attachment: &twilight_model::channel::Attachment) -> bool {
+    attachment.filename.starts_with(SPOILER_PREFIX)
+}
+
+fn sticker_url(sticker: &MessageSticker) -> Result<String, Error> {
+    Ok(format!(
+        "https://cdn.discordapp.com/stickers/{}.{}",
+        sticker.id,
+        match sticker.format_type {
+            StickerFormatType::Gif => "gif",
+            StickerFormatType::Png | StickerFormatType::Apng => "png",
+            _ => return Err(Error::StickerLinkInvalid),
+        }
+    ))
+}
+
+const fn empty_embed() -> Embed {
+    Embed {
+        author: None,
+        color: None,
+        description: None,
+        fields: vec![],
+        footer: None,
+        image: None,
+        kind: String::new(),
+        provider: None,
+        thumbnail: None,
+        timestamp: None,
+        title: None,
+        url: None,
+        video: None,
+    }
+}
+
+/// Return the combined size limit of attachments to re-upload in a guild with
+/// the given premium tier
+///
+/// The base limit is 10 MiB, it's raised to 50 MiB with the second premium
+/// tier and 100 MiB with the third
+#[cfg(feature = "upload")]
+const fn upload_size_limit(premium_tier: PremiumTier) -> u64 {
+    match premium_tier {
+        PremiumTier::Tier2 => 50 * 1024 * 1024,
+        PremiumTier::Tier3 => 100 * 1024 * 1024,
+        _ => 10 * 1024 * 1024,
+    }
 }
 
 #[cfg(feature = "upload")]
 impl<'a> MessageSource<'a> {
     /// Re-upload the attachments
     ///
-    /// This downloads and saves the attachments, they're later uploaded in
-    /// [`MessageSource::create`]
+    /// This downloads and saves the attachments concurrently, they're later
+    /// uploaded in [`MessageSource::create`], if [`DownloadOptions::deferred`]
+    /// is set, they're instead downloaded in [`MessageSource::create`]
+    ///
+    /// The downloaded files are released after the message is created
+    ///
+    /// Embed images, thumbnails and author icons that link to the attachments
+    /// are changed to refer to the re-uploaded files
+    ///
+    /// The attachments' file names, so their spoiler status, and alt text are
+    /// kept, other fields such as the content type are set by Discord, voice
+    /// message fields such as the waveform can't be set by webhooks
     ///
     /// # Warnings
     ///
     /// This is an expensive operation since it means downloading and uploading
-    /// up to 25 MBs
+    /// up to 10 MiB, or up to 100 MiB in boosted guilds
     ///
     /// # Errors
     ///
     /// Returns [`Error::AttachmentTooLarge`] if the combined size of the
-    /// attachments is over 25 MB
+    /// attachments is over the upload limit, see [`Info::upload_size_limit`]
+    ///
+    /// Returns [`Error::Http`] or [`Error::DeserializeBody`] if getting the
+    /// guild's premium tier fails
     ///
-    /// Returns [`Error::Reqwest`] if downloading the attachments fails
+    /// Returns the error of [`AttachmentFetcher::fetch`] if downloading the
+    /// attachments fails, [`Error::Reqwest`] by default, see
+    /// [`MessageSource::download_options`]
     pub async fn handle_attachment_upload(mut self) -> Result<MessageSource<'a>, Error> {
         if self
             .attachment_sticker_info
@@ -133,29 +670,328 @@ impl<'a> MessageSource<'a> {
             .iter()
             .map(|attachment| attachment.size)
             .sum::<u64>()
-            > 25 * 1024 * 1024
+            > self.upload_size_limit().await?
         {
             return Err(Error::AttachmentTooLarge);
         }
 
-        let client = Client::new();
-        for attachment in self.attachment_sticker_info.attachments {
-            self.attachment_sticker_info.attachments_upload.push(
-                twilight_model::http::attachment::Attachment {
-                    description: attachment.description.clone(),
-                    file: client
-                        .get(&attachment.url)
-                        .send()
-                        .await?
-                        .bytes()
-                        .await?
-                        .to_vec(),
-                    filename: attachment.filename.clone(),
-                    id: attachment.id.get(),
-                },
-            );
+        self.set_attachments_upload(self.attachment_sticker_info.attachments.iter().collect())
+            .await?;
+
+        Ok(self)
+    }
+
+    /// Re-upload the attachments that fit the upload limit and link the rest
+    ///
+    /// The upload limit is resolved like in
+    /// [`MessageSource::handle_attachment_upload`]
+    ///
+    /// Attachments are picked to re-upload in the order of the given
+    /// [`UploadPriority`], an attachment that doesn't fit is skipped and the
+    /// next ones are still tried, the re-uploaded attachments keep their order
+    /// in the message
+    ///
+    /// The attachments that are re-uploaded are in
+    /// [`Info::attachments_upload`] and the ones that are linked are in
+    /// [`Info::attachments_link`], the links are appended like in
+    /// [`MessageSource::handle_attachment_link`]
+    ///
+    /// # Warnings
+    ///
+    /// This is an expensive operation since it means downloading and uploading
+    /// up to 10 MiB, or up to 100 MiB in boosted guilds
+    ///
+    /// The links will die after the source message is deleted, they also
+    /// expire after some time, see [`MessageSource::refresh_attachment_urls`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ContentInvalid`] if the message content becomes
+    /// too long after adding the links and
+    /// [`Options::split_content`](crate::constructor::Options::split_content)
+    /// isn't set
+    ///
+    /// Returns [`Error::Http`] or [`Error::DeserializeBody`] if getting the
+    /// guild's premium tier fails
+    ///
+    /// Returns the error of [`AttachmentFetcher::fetch`] if downloading the
+    /// attachments fails, [`Error::Reqwest`] by default, see
+    /// [`MessageSource::download_options`]
+    pub async fn handle_attachment_upload_or_link(
+        mut self,
+        priority: UploadPriority,
+    ) -> Result<MessageSource<'a>, Error> {
+        let attachments = self.attachment_sticker_info.attachments;
+
+        let mut picked = attachments.iter().collect::<Vec<_>>();
+        match priority {
+            UploadPriority::Order => {}
+            UploadPriority::ImagesFirst => picked.sort_by_key(|attachment| {
+                !attachment
+                    .content_type
+                    .as_ref()
+                    .is_some_and(|content_type| content_type.starts_with("image/"))
+            }),
+            UploadPriority::SmallestFirst => picked.sort_by_key(|attachment| attachment.size),
+        }
+
+        let mut size_left = self.upload_size_limit().await?;
+        picked.retain(|attachment| {
+            let Some(size) = size_left.checked_sub(attachment.size) else {
+                return false;
+            };
+            size_left = size;
+            true
+        });
+
+        let (upload, link): (Vec<_>, Vec<_>) = attachments.iter().partition(|attachment| {
+            picked
+                .iter()
+                .any(|picked_attachment| picked_attachment.id == attachment.id)
+        });
+
+        if !link.is_empty() {
+            let links = link
+                .iter()
+                .map(|attachment| self.attachment_link(attachment))
+                .collect::<Vec<_>>();
+            self.append_urls(links.iter().map(String::as_str))?;
+        }
+
+        self.set_attachments_upload(upload).await?;
+        self.attachment_sticker_info.attachments_link = link;
+
+        Ok(self)
+    }
+
+    async fn upload_size_limit(&mut self) -> Result<u64, Error> {
+        if let Some(limit) = self.attachment_sticker_info.upload_size_limit {
+            return Ok(limit);
+        }
+
+        self.set_guild_premium_tier().await?;
+
+        Ok(upload_size_limit(self.guild_premium_tier.unwrap()))
+    }
+
+    /// Re-upload the stickers as images
+    ///
+    /// This downloads the stickers and saves them as attachments, they're later
+    /// uploaded in [`MessageSource::create`], unlike
+    /// [`MessageSource::handle_sticker_link`], [`StickerFormatType::Apng`]
+    /// stickers stay animated
+    ///
+    /// Stickers are downloaded right away even if
+    /// [`DownloadOptions::deferred`] is set since they're small
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::StickerLinkInvalid`] if a sticker's
+    /// [`StickerFormatType`] is [`StickerFormatType::Unknown`], or
+    /// [`StickerFormatType::Lottie`] and `lottie` is [`LottieSticker::Error`]
+    ///
+    /// Returns [`Error::ContentInvalid`] if `lottie` is
+    /// [`LottieSticker::Placeholder`] and the message content becomes too long
+    /// after adding the sticker names and
+    /// [`Options::split_content`](crate::constructor::Options::split_content)
+    /// isn't set
+    #[cfg_attr(
+        feature = "lottie",
+        doc = "",
+        doc = " Returns [`Error::LottieRender`] if `lottie` is `LottieSticker::Render`",
+        doc = " and rendering a sticker fails"
+    )]
+    ///
+    /// Returns the error of [`AttachmentFetcher::fetch_url`] if downloading
+    /// the stickers fails, [`Error::Reqwest`] by default, see
+    /// [`MessageSource::download_options`]
+    pub async fn handle_sticker_upload(
+        mut self,
+        lottie: LottieSticker,
+    ) -> Result<MessageSource<'a>, Error> {
+        if lottie == LottieSticker::Placeholder {
+            let name_lines = self
+                .attachment_sticker_info
+                .stickers
+                .iter()
+                .filter(|sticker| sticker.format_type == StickerFormatType::Lottie)
+                .map(sticker_name_line)
+                .collect::<Vec<_>>();
+
+            if !name_lines.is_empty() {
+                self.append_urls(name_lines.iter().map(String::as_str))?;
+            }
+        }
+
+        for sticker in self.attachment_sticker_info.stickers {
+            let name = sticker_file_name(sticker);
+
+            let attachment = match (sticker.format_type, lottie) {
+                (StickerFormatType::Lottie, LottieSticker::Placeholder) => {
+                    twilight_model::http::attachment::Attachment {
+                        description: Some(sticker.name.clone()),
+                        file: STICKER_PLACEHOLDER.to_vec(),
+                        filename: format!("{name}.png"),
+                        id: sticker.id.get(),
+                    }
+                }
+                #[cfg(feature = "lottie")]
+                (StickerFormatType::Lottie, LottieSticker::Render) => {
+                    let url = format!("https://discord.com/stickers/{}.json", sticker.id);
+
+                    let json = self.download_options.fetcher().fetch_url(&url).await?;
+                    let file =
+                        tokio::task::spawn_blocking(move || crate::lottie::render_gif(&json))
+                            .await
+                            .map_err(|_| Error::LottieRender)??;
+
+                    twilight_model::http::attachment::Attachment {
+                        description: Some(sticker.name.clone()),
+                        file,
+                        filename: format!("{name}.gif"),
+                        id: sticker.id.get(),
+                    }
+                }
+                _ => {
+                    let url = sticker_url(sticker)?;
+                    let extension = if sticker.format_type == StickerFormatType::Gif {
+                        "gif"
+                    } else {
+                        "png"
+                    };
+
+                    twilight_model::http::attachment::Attachment {
+                        description: Some(sticker.name.clone()),
+                        file: self.download_options.fetcher().fetch_url(&url).await?,
+                        filename: format!("{name}.{extension}"),
+                        id: sticker.id.get(),
+                    }
+                }
+            };
+
+            self.attachment_sticker_info
+                .attachments_upload
+                .push(attachment);
         }
 
         Ok(self)
     }
+
+    /// Return the memory used by the attachments to re-upload
+    ///
+    /// Sum this over message sources to estimate the memory used by them, for
+    /// example ones returned from [`MessageSource::later_messages`]
+    #[must_use]
+    pub fn attachment_memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            buffered: self
+                .attachment_sticker_info
+                .attachments_upload
+                .iter()
+                .map(|attachment| u64::try_from(attachment.file.len()).unwrap_or(u64::MAX))
+                .fold(0, u64::saturating_add),
+            deferred: self
+                .attachment_sticker_info
+                .attachments_deferred
+                .iter()
+                .map(|attachment| attachment.size)
+                .fold(0, u64::saturating_add),
+        }
+    }
+
+    /// Download the deferred attachments, see [`DownloadOptions::deferred`]
+    pub(crate) async fn download_deferred_attachments(&mut self) -> Result<(), Error> {
+        let attachments = std::mem::take(&mut self.attachment_sticker_info.attachments_deferred);
+        if !attachments.is_empty() {
+            let attachments_upload = self.download(attachments).await?;
+            self.attachment_sticker_info
+                .attachments_upload
+                .extend(attachments_upload);
+        }
+
+        Ok(())
+    }
+
+    /// Release the downloaded files after they're uploaded
+    pub(crate) fn release_attachments(&mut self) {
+        self.attachment_sticker_info.attachments_upload = vec![];
+    }
+
+    /// Download the attachments or defer downloading them, see
+    /// [`DownloadOptions::deferred`]
+    async fn set_attachments_upload(
+        &mut self,
+        attachments: Vec<&'a twilight_model::channel::Attachment>,
+    ) -> Result<(), Error> {
+        self.rewrite_embed_urls(&attachments);
+
+        if self.download_options.deferred {
+            self.attachment_sticker_info.attachments_deferred = attachments;
+        } else {
+            let attachments_upload = self.download(attachments).await?;
+            self.attachment_sticker_info
+                .attachments_upload
+                .extend(attachments_upload);
+        }
+
+        Ok(())
+    }
+
+    /// Point the embeds' images, thumbnails and author icons that are
+    /// re-uploaded attachments to the re-uploaded files, since the original
+    /// links die after the source message is deleted
+    fn rewrite_embed_urls(&mut self, attachments: &[&twilight_model::channel::Attachment]) {
+        let attachment_ref = |url: &str| {
+            attachments
+                .iter()
+                .find(|attachment| {
+                    [&attachment.url, &attachment.proxy_url]
+                        .iter()
+                        .any(|attachment_url| url_path(attachment_url) == url_path(url))
+                })
+                .map(|attachment| format!("attachment://{}", attachment.filename))
+        };
+
+        for embed in &mut self.embeds {
+            if let Some(image) = &mut embed.image {
+                if let Some(url) = attachment_ref(&image.url) {
+                    image.url = url;
+                    image.proxy_url = None;
+                }
+            }
+            if let Some(thumbnail) = &mut embed.thumbnail {
+                if let Some(url) = attachment_ref(&thumbnail.url) {
+                    thumbnail.url = url;
+                    thumbnail.proxy_url = None;
+                }
+            }
+            if let Some(author) = &mut embed.author {
+                if let Some(url) = author.icon_url.as_deref().and_then(attachment_ref) {
+                    author.icon_url = Some(url);
+                    author.proxy_icon_url = None;
+                }
+            }
+        }
+    }
+
+    /// Download the attachments concurrently, keeping their order
+    async fn download(
+        &self,
+        attachments: Vec<&'a twilight_model::channel::Attachment>,
+    ) -> Result<Vec<twilight_model::http::attachment::Attachment>, Error> {
+        let options = &self.download_options;
+
+        stream::iter(attachments)
+            .map(|attachment| async move {
+                Ok::<_, Error>(twilight_model::http::attachment::Attachment {
+                    description: attachment.description.clone(),
+                    file: options.fetcher().fetch(attachment).await?,
+                    filename: attachment.filename.clone(),
+                    id: attachment.id.get(),
+                })
+            })
+            .buffered(options.concurrency.max(1))
+            .try_collect()
+            .await
+    }
 }
@@ -18,7 +18,9 @@ pub struct Info {
     /// Avatar owner's user ID
     pub user_id: Id<UserMarker>,
     /// Member avatar owner's guild ID
-    pub guild_id: Id<GuildMarker>,
+    ///
+    /// `None` if the avatar owner isn't a member, for example in DMs
+    pub guild_id: Option<Id<GuildMarker>>,
     /// Avatar owner's discriminator
     pub user_discriminator: u16,
     /// Avatar owner's global avatar hash
@@ -32,12 +34,10 @@ pub struct Info {
 }
 
 impl Info {
-    #[allow(clippy::option_if_let_else)]
     pub(crate) fn set_url(&mut self) {
-        let url = if let Some(avatar) = self.member_avatar {
+        let url = if let (Some(avatar), Some(guild_id)) = (self.member_avatar, self.guild_id) {
             format!(
-                "https://cdn.discordapp.com/guilds/{}/users/{}/avatars/{avatar}.{}",
-                self.guild_id,
+                "https://cdn.discordapp.com/guilds/{guild_id}/users/{}/avatars/{avatar}.{}",
                 self.user_id,
                 hash_extension(avatar)
             )
@@ -1,14 +1,64 @@
+//! Creating [`MessageSource`] from a [`Message`]
+
+use std::time::Duration;
+
 use twilight_http::Client;
-use twilight_model::channel::{
-    message::{MessageFlags, MessageType},
-    Message,
+use twilight_model::{
+    channel::{
+        message::{Embed, MessageFlags, MessageType},
+        Message,
+    },
+    id::{
+        marker::{ChannelMarker, GuildMarker},
+        Id,
+    },
 };
 
 use crate::{
-    attachment_sticker, avatar, component, error::Error, later_messages, reaction, reference,
-    thread, MessageSource,
+    attachment_sticker, avatar, component, earlier_messages, error::Error, interaction,
+    later_messages, reaction, reference, rich_presence, system, thread, MessageSource,
 };
 
+/// Options for messages that are otherwise rejected when creating
+/// [`MessageSource`]
+///
+/// Options are also used when creating [`MessageSource`] for later messages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Options {
+    /// Render system messages as text, for example *X pinned a message*,
+    /// instead of returning [`Error::System`]
+    pub render_system: bool,
+    /// Clone voice messages as normal audio attachments with a note of their
+    /// duration, instead of returning [`Error::Voice`]
+    ///
+    /// The audio file still has to be re-uploaded with
+    /// [`MessageSource::handle_attachment_upload`]
+    #[cfg(feature = "upload")]
+    pub upload_voice: bool,
+    /// Clone messages related to rich presence, such as activity invites, with
+    /// an embed describing the activity, instead of returning
+    /// [`Error::RichPresence`]
+    ///
+    /// The embed is labelled as a non-interactive copy, since bots can't
+    /// create activity invites
+    pub render_rich_presence: bool,
+    /// Split content over [`MESSAGE_CONTENT_LENGTH_MAX`] into multiple
+    /// messages, instead of returning [`Error::ContentInvalid`]
+    ///
+    /// This may happen when the author has used Nitro perks to send a message
+    /// with over 2000 characters, or when the content becomes too long after
+    /// handling attachments, stickers or interactions
+    ///
+    /// The content is split at newlines, or spaces if there are none,
+    /// code blocks are closed and reopened in the next message
+    ///
+    /// See [`MessageSource::create`] for more
+    ///
+    /// [`MESSAGE_CONTENT_LENGTH_MAX`]: twilight_validate::message::MESSAGE_CONTENT_LENGTH_MAX
+    pub split_content: bool,
+}
+
 impl<'a> MessageSource<'a> {
     /// Create [`MessageSource`] from a [`Message`]
     ///
@@ -28,32 +78,79 @@ impl<'a> MessageSource<'a> {
     /// bots currently can't create
     ///
     /// Returns [`Error::System`] if the message's type isn't
-    /// [`MessageType::Regular`] or [`MessageType::Reply`] or has role
-    /// subscription data, which are edge-cases that can't be replicated
-    /// correctly
+    /// [`MessageType::Regular`], [`MessageType::Reply`],
+    /// [`MessageType::ChatInputCommand`] or [`MessageType::ContextMenuCommand`]
+    /// or has role subscription data, which are edge-cases that can't be
+    /// replicated correctly
     ///
     /// Returns [`Error::ContentInvalid`] if the message's content is
     /// invalid, this may happen when the author has used Nitro perks to send a
     /// message with over 2000 characters
+    ///
+    /// Returns [`Error::MessageValidation`] if
+    /// [`Options::render_rich_presence`] is set and the message already has
+    /// [`EMBED_COUNT_LIMIT`] embeds
+    ///
+    /// [`EMBED_COUNT_LIMIT`]: twilight_validate::message::EMBED_COUNT_LIMIT
     pub fn from_message(message: &'a Message, http: &'a Client) -> Result<Self, Error> {
-        if message.activity.is_some() || message.application.is_some() {
-            return Err(Error::RichPresence);
-        }
-        if message
-            .flags
-            .is_some_and(|flags| flags.contains(MessageFlags::IS_VOICE_MESSAGE))
-        {
-            return Err(Error::Voice);
-        }
-        if !matches!(message.kind, MessageType::Regular | MessageType::Reply)
-            || message.role_subscription_data.is_some()
-        {
-            return Err(Error::System);
-        }
-        twilight_validate::message::content(&message.content).map_err(|_| Error::ContentInvalid)?;
+        Self::from_message_with(message, http, Options::default())
+    }
 
+    /// Create [`MessageSource`] from a [`Message`] with the given [`Options`]
+    ///
+    /// # Errors
+    ///
+    /// See [`MessageSource::from_message`], except for the errors disabled by
+    /// `options`
+    pub fn from_message_with(
+        message: &'a Message,
+        http: &'a Client,
+        options: Options,
+    ) -> Result<Self, Error> {
         let guild_id = message.guild_id.ok_or(Error::NotInGuild)?;
 
+        Self::new(message, Some(guild_id), guild_id, options, http)
+    }
+
+    /// Create [`MessageSource`] from a [`Message`] in a DM or group DM
+    ///
+    /// Since a webhook can't be created in a DM, the message is cloned to the
+    /// given channel in the given guild
+    ///
+    /// The message's avatar will be the author's global avatar since they
+    /// don't have a guild avatar in DMs
+    ///
+    /// This can also be used for messages in guilds, in which case it's the
+    /// same as [`MessageSource::from_message`] followed by setting the
+    /// channel
+    ///
+    /// # Errors
+    ///
+    /// See [`MessageSource::from_message_with`], except for
+    /// [`Error::NotInGuild`]
+    pub fn from_dm_message(
+        message: &'a Message,
+        http: &'a Client,
+        channel_id: Id<ChannelMarker>,
+        guild_id: Id<GuildMarker>,
+        options: Options,
+    ) -> Result<Self, Error> {
+        let mut source = Self::new(message, message.guild_id, guild_id, options, http)?;
+        source.channel_id = channel_id;
+
+        Ok(source)
+    }
+
+    pub(crate) fn new(
+        message: &'a Message,
+        source_guild_id: Option<Id<GuildMarker>>,
+        guild_id: Id<GuildMarker>,
+        options: Options,
+        http: &'a Client,
+    ) -> Result<Self, Error> {
+        let embeds = embeds(message, options)?;
+        let content = content(message, source_guild_id, options)?;
+
         let url_components = component::filter_valid(&message.components);
         let has_invalid_components = message.components != url_components;
 
@@ -79,13 +176,18 @@ impl<'a> MessageSource<'a> {
             source_id: message.id,
             source_channel_id: message.channel_id,
             source_thread_id: thread_info.id(),
-            content: message.content.clone(),
-            embeds: message.embeds.clone(),
+            content,
+            embeds,
             tts: message.tts,
-            flags: message.flags,
+            flags: message
+                .flags
+                .map(|flags| flags.difference(MessageFlags::IS_VOICE_MESSAGE)),
             channel_id: message.channel_id,
+            source_guild_id,
             guild_id,
             guild_emoji_ids: None,
+            #[cfg(feature = "upload")]
+            guild_premium_tier: None,
             username: message
                 .member
                 .as_ref()
@@ -96,7 +198,7 @@ impl<'a> MessageSource<'a> {
             avatar_info: avatar::Info {
                 url: None,
                 user_id: message.author.id,
-                guild_id,
+                guild_id: source_guild_id,
                 user_discriminator: message.author.discriminator,
                 user_avatar: message.author.avatar,
                 member_avatar: message.member.as_ref().and_then(|member| member.avatar),
@@ -108,23 +210,123 @@ impl<'a> MessageSource<'a> {
             attachment_sticker_info: attachment_sticker::Info {
                 stickers: &message.sticker_items,
                 attachments: &message.attachments,
+                refreshed_urls: None,
                 #[cfg(feature = "upload")]
                 attachments_upload: vec![],
+                #[cfg(feature = "upload")]
+                attachments_deferred: vec![],
+                #[cfg(feature = "upload")]
+                attachments_link: vec![],
+                #[cfg(feature = "upload")]
+                upload_size_limit: None,
             },
+            #[cfg(feature = "upload")]
+            download_options: attachment_sticker::DownloadOptions::default(),
             component_info: component::Info {
                 url_components,
                 has_invalid_components,
             },
+            interaction_info: interaction::Info {
+                interaction: message.interaction.as_ref(),
+                is_chat_input: message.kind == MessageType::ChatInputCommand,
+            },
             thread_info,
+            thread_archived: None,
             webhook: None,
+            earlier_messages: earlier_messages::Info {
+                messages: vec![],
+                source_message: None,
+                is_earlier_message_sources_created: false,
+            },
             later_messages: later_messages::Info {
                 messages: vec![],
                 is_complete: false,
                 is_source_created: false,
+                created_message_count: 0,
+                until: later_messages::Until::End,
+                last_message_id: None,
                 is_later_message_sources_created: false,
             },
             response: None,
+            options,
             http,
         })
     }
 }
+
+/// Return the content of the message, handling the options for voice and
+/// system messages
+fn content(
+    message: &Message,
+    source_guild_id: Option<Id<GuildMarker>>,
+    options: Options,
+) -> Result<String, Error> {
+    let is_voice = message
+        .flags
+        .is_some_and(|flags| flags.contains(MessageFlags::IS_VOICE_MESSAGE));
+    #[cfg(feature = "upload")]
+    let is_voice_uploaded = is_voice && options.upload_voice;
+    #[cfg(not(feature = "upload"))]
+    let is_voice_uploaded = false;
+    if is_voice && !is_voice_uploaded {
+        return Err(Error::Voice);
+    }
+
+    let content = if is_voice_uploaded {
+        voice_note(message)
+    } else if !matches!(
+        message.kind,
+        MessageType::Regular
+            | MessageType::Reply
+            | MessageType::ChatInputCommand
+            | MessageType::ContextMenuCommand
+    ) || message.role_subscription_data.is_some()
+    {
+        if !options.render_system {
+            return Err(Error::System);
+        }
+        system::content(message, source_guild_id)?
+    } else {
+        message.content.clone()
+    };
+    if !options.split_content {
+        twilight_validate::message::content(&content).map_err(|_| Error::ContentInvalid)?;
+    }
+
+    Ok(content)
+}
+
+/// Return the embeds of the message, handling the option for rich presence
+fn embeds(message: &Message, options: Options) -> Result<Vec<Embed>, Error> {
+    let is_rich_presence = message.activity.is_some() || message.application.is_some();
+    if is_rich_presence && !options.render_rich_presence {
+        return Err(Error::RichPresence);
+    }
+
+    let mut embeds = message.embeds.clone();
+    if is_rich_presence {
+        embeds.push(rich_presence::embed(message));
+        twilight_validate::message::embeds(&embeds)?;
+    }
+
+    Ok(embeds)
+}
+
+fn voice_note(message: &Message) -> String {
+    message
+        .attachments
+        .first()
+        .and_then(|attachment| attachment.duration_secs)
+        .and_then(|duration_secs| Duration::try_from_secs_f64(duration_secs).ok())
+        .map_or_else(
+            || "*Voice message*".to_owned(),
+            |duration| {
+                let secs = duration.as_secs();
+                format!(
+                    "*Voice message ({}:{:02})*",
+                    secs.div_euclid(60),
+                    secs.rem_euclid(60)
+                )
+            },
+        )
+}
@@ -15,8 +15,9 @@ struct MessagesDelete {
 impl<'a> MessageSource<'a> {
     /// Check if [`MessageSource::delete`] would use more than `n` requests
     ///
-    /// If [`MessageSource::later_messages`] or
-    /// [`MessageSource::later_messages_batched`] wasn't called, `n` is always 1
+    /// If [`MessageSource::earlier_messages`], [`MessageSource::later_messages`]
+    /// or [`MessageSource::later_messages_batched`] wasn't called, `n` is
+    /// always 1
     ///
     /// Each message older than 2 weeks uses 1 request, others use
     /// `other_message_count` divided by
@@ -46,7 +47,8 @@ impl<'a> MessageSource<'a> {
     ///
     /// If [`MessageSource::later_messages`] or
     /// [`MessageSource::later_messages_batched`] was called, later messages
-    /// will also be deleted
+    /// will also be deleted, the same goes for
+    /// [`MessageSource::earlier_messages`] and earlier messages
     ///
     /// If there is a message older than two weeks, they'll be
     /// deleted individually since bulk delete isn't valid for these messages,
@@ -83,6 +85,15 @@ impl<'a> MessageSource<'a> {
     fn messages_delete(&self) -> MessagesDelete {
         let mut message_ids = vec![self.source_id];
 
+        if self.earlier_messages.is_earlier_message_sources_created {
+            message_ids.extend(
+                self.earlier_messages
+                    .messages
+                    .iter()
+                    .map(|message| message.id),
+            );
+        }
+
         if self.later_messages.is_later_message_sources_created {
             message_ids.extend(
                 self.later_messages
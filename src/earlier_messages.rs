@@ -0,0 +1,229 @@
+//! Handling the messages sent before the message
+
+#[cfg(doc)]
+use twilight_model::guild::Permissions;
+use twilight_model::{
+    channel::Message,
+    id::{marker::MessageMarker, Id},
+};
+
+use crate::{error::Error, MessageSource};
+
+/// Info about the earlier messages in the channel
+#[derive(Debug, Clone, PartialEq)]
+pub struct Info {
+    /// Messages sent earlier, in chronological order
+    pub messages: Vec<Message>,
+    /// Source message fetched in [`MessageSource::ordered_messages`]
+    pub source_message: Option<Message>,
+    /// Whether [`MessageSource::earlier_messages`] or
+    /// [`MessageSource::ordered_messages`] was called
+    pub is_earlier_message_sources_created: bool,
+}
+
+/// Where the earlier messages start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Since {
+    /// Since the given number of messages before the source message
+    Count(usize),
+    /// Since the message with the given ID, including it
+    Message(Id<MessageMarker>),
+}
+
+impl MessageSource<'_> {
+    /// Return [`MessageSource`] for messages sent before this, in
+    /// chronological order
+    ///
+    /// Make sure the bot has these additional permissions
+    /// - [`Permissions::READ_MESSAGE_HISTORY`]
+    /// - [`Permissions::VIEW_CHANNEL`]
+    ///
+    /// To clone the messages in order, create the returned message sources,
+    /// then call [`MessageSource::create`], then create the message sources
+    /// returned from [`MessageSource::later_messages`] or
+    /// [`MessageSource::later_messages_batched`], see
+    /// [`MessageSource::ordered_messages`] to get them in one vector instead
+    ///
+    /// Returned message sources don't implicitly call the same methods as
+    /// `self`, see [`MessageSource::later_messages`]
+    ///
+    /// This also gets the last message in the channel, messages after it
+    /// aren't considered later messages, so that the earlier messages cloned
+    /// to the same channel aren't cloned again, see
+    /// [`later_messages::Info::last_message_id`]
+    ///
+    /// # Warnings
+    ///
+    /// If the bot doesn't have [`Permissions::READ_MESSAGE_HISTORY`], it'll
+    /// always return an empty vector, since that's what Discord responds with
+    ///
+    /// # Errors
+    ///
+    /// The vector element will be an error if the message can't be resent (See
+    /// [`MessageSource::from_message_with`], the options of `self` are used)
+    ///
+    /// Returns [`Error::Http`] if getting channel messages fails
+    ///
+    /// Returns [`Error::DeserializeBody`] if deserializing channel messages
+    /// fails
+    ///
+    /// [`later_messages::Info::last_message_id`]: crate::later_messages::Info::last_message_id
+    pub async fn earlier_messages(
+        &mut self,
+        since: Since,
+    ) -> Result<Vec<Result<MessageSource<'_>, Error>>, Error> {
+        if !self.later_messages.is_complete {
+            self.later_messages.last_message_id = self
+                .http
+                .channel_messages(self.source_thread_id.unwrap_or(self.source_channel_id))
+                .limit(1)?
+                .await?
+                .models()
+                .await?
+                .first()
+                .map(|message| message.id);
+        }
+
+        self.set_earlier_messages(since).await?;
+        self.earlier_messages.is_earlier_message_sources_created = true;
+
+        Ok(self.message_sources(&self.earlier_messages.messages))
+    }
+
+    /// Return [`MessageSource`] for messages sent before this, this message
+    /// and messages sent after this, in chronological order
+    ///
+    /// This combines [`MessageSource::earlier_messages`] and
+    /// [`MessageSource::later_messages`] into one batch, all messages are
+    /// fetched before any of them is created, so the message sources can
+    /// simply be created in order, [`MessageSource::create`] shouldn't be
+    /// called on `self`
+    ///
+    /// Make sure the bot has these additional permissions
+    /// - [`Permissions::READ_MESSAGE_HISTORY`]
+    /// - [`Permissions::VIEW_CHANNEL`]
+    ///
+    /// # Warnings
+    ///
+    /// The message source for this message is created from the message fetched
+    /// again, so methods called on `self` and fields mutated on it don't apply
+    /// to it, like the other returned message sources, except for the username
+    /// and avatar, which are copied from `self`
+    ///
+    /// This method is potentially very expensive unless
+    /// [`MessageSource::later_messages_until`] was called
+    ///
+    /// Should not be combined with [`MessageSource::earlier_messages`],
+    /// [`MessageSource::later_messages`] or
+    /// [`MessageSource::later_messages_batched`]
+    ///
+    /// # Errors
+    ///
+    /// The vector element will be an error if the message can't be resent (See
+    /// [`MessageSource::from_message_with`], the options of `self` are used)
+    ///
+    /// Returns [`Error::Http`] if getting channel messages fails
+    ///
+    /// Returns [`Error::DeserializeBody`] if deserializing channel messages
+    /// fails
+    pub async fn ordered_messages(
+        &mut self,
+        since: Since,
+    ) -> Result<Vec<Result<MessageSource<'_>, Error>>, Error> {
+        self.set_earlier_messages(since).await?;
+        self.set_later_messages(None).await?;
+
+        let mut source_message = self
+            .http
+            .message(
+                self.source_thread_id.unwrap_or(self.source_channel_id),
+                self.source_id,
+            )
+            .await?
+            .model()
+            .await?;
+        source_message.guild_id = self.source_guild_id;
+        self.earlier_messages.source_message = Some(source_message);
+
+        self.earlier_messages.is_earlier_message_sources_created = true;
+        self.later_messages.is_later_message_sources_created = true;
+
+        let mut sources = self.message_sources(&self.earlier_messages.messages);
+        if let Some(message) = &self.earlier_messages.source_message {
+            sources.extend(
+                self.message_sources(std::slice::from_ref(message))
+                    .into_iter()
+                    .map(|source| {
+                        // messages fetched by the bot don't have the member
+                        source.map(|mut source_inner| {
+                            source_inner.username.clone_from(&self.username);
+                            source_inner.avatar_info = self.avatar_info.clone();
+                            source_inner
+                        })
+                    }),
+            );
+        }
+        sources.extend(self.message_sources(&self.later_messages.messages));
+
+        Ok(sources)
+    }
+
+    async fn set_earlier_messages(&mut self, since: Since) -> Result<(), Error> {
+        // newest first until reversed
+        let mut messages: Vec<Message> = vec![];
+
+        loop {
+            let limit = match since {
+                Since::Count(count) => count.saturating_sub(messages.len()).min(100),
+                Since::Message(_) => 100,
+            };
+            if limit == 0 {
+                break;
+            }
+
+            let mut message_batch = self
+                .http
+                .channel_messages(self.source_thread_id.unwrap_or(self.source_channel_id))
+                .limit(limit.try_into().unwrap_or(100))?
+                .before(
+                    messages
+                        .last()
+                        .map_or(self.source_id, |message: &Message| message.id),
+                )
+                .await?
+                .models()
+                .await?;
+
+            for message in &mut message_batch {
+                message.guild_id = self.source_guild_id;
+            }
+
+            let is_complete = message_batch.len() < limit;
+
+            if let Since::Message(message_id) = since {
+                if let Some(end) = message_batch
+                    .iter()
+                    .position(|message| message.id <= message_id)
+                {
+                    let is_included = message_batch
+                        .get(end)
+                        .is_some_and(|message| message.id == message_id);
+                    message_batch.truncate(end.saturating_add(usize::from(is_included)));
+                    messages.extend(message_batch);
+                    break;
+                }
+            }
+
+            messages.extend(message_batch);
+
+            if is_complete {
+                break;
+            }
+        }
+
+        messages.reverse();
+        self.earlier_messages.messages = messages;
+
+        Ok(())
+    }
+}
@@ -47,10 +47,20 @@ pub enum Error {
     /// Message's attachments are too large
     ///
     /// This happens when the author has used Nitro perks to send a message with
-    /// over 25 MB of attachments
+    /// attachments over the upload limit of the guild the message is cloned to
     #[cfg(feature = "upload")]
     #[error("message's attachments are too large")]
     AttachmentTooLarge,
+    /// Message is a media channel post without attachments to upload
+    ///
+    /// Posts in media channels require an attachment, links to the
+    /// attachments don't count
+    #[error("message is a media channel post without attachments to upload")]
+    MediaPostAttachment,
+    /// Thread the message is created in is locked and the bot doesn't have
+    /// the permissions to unlock it
+    #[error("thread is locked and the bot doesn't have permissions to unlock it")]
+    ThreadLocked,
     /// Message's content is invalid
     #[error("message's content is invalid")]
     ContentInvalid,
@@ -82,4 +92,13 @@ pub enum Error {
     #[cfg(feature = "upload")]
     #[error("{0}")]
     Reqwest(#[from] reqwest::Error),
+    /// Sticker's Lottie animation couldn't be rendered
+    #[cfg(feature = "lottie")]
+    #[error("sticker's lottie animation couldn't be rendered")]
+    LottieRender,
+    /// A custom [`AttachmentFetcher`](crate::attachment_sticker::AttachmentFetcher)
+    /// failed to fetch an attachment
+    #[cfg(feature = "upload")]
+    #[error("fetching attachment failed: {0}")]
+    Fetch(#[source] Box<dyn std::error::Error + Send + Sync>),
 }
@@ -0,0 +1,130 @@
+//! Handling the message being a response to an application command
+
+use twilight_model::channel::message::{embed::EmbedAuthor, Embed, MessageInteraction};
+use twilight_validate::message::MESSAGE_CONTENT_LENGTH_MAX;
+
+use crate::{avatar, error::Error, MessageSource};
+
+/// Where to put the header of the interaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    /// Put the header in an embed before the other embeds
+    ///
+    /// The embed's author will be the user who used the command, with their
+    /// avatar
+    Embed,
+    /// Put the header in a line before the message content
+    ContentPrefix,
+}
+
+/// Info about the interaction the message is a response to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Info<'a> {
+    /// The interaction the message is a response to
+    ///
+    /// `None` if the message isn't a response to an interaction
+    pub interaction: Option<&'a MessageInteraction>,
+    /// Whether the interaction is a slash command, as opposed to a context
+    /// menu command
+    pub is_chat_input: bool,
+}
+
+impl MessageSource<'_> {
+    /// Handle the message being a response to an application command
+    ///
+    /// This adds a header like *X used /command*, which the client shows
+    /// above such messages
+    ///
+    /// # Warnings
+    ///
+    /// Must be called before [`MessageSource::create`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MessageValidation`] if [`Placement::Embed`] is passed
+    /// and the message already has [`EMBED_COUNT_LIMIT`] embeds
+    ///
+    /// Returns [`Error::ContentInvalid`] if [`Placement::ContentPrefix`] is
+    /// passed and the message content becomes too long after adding the
+    /// header and
+    /// [`Options::split_content`](crate::constructor::Options::split_content)
+    /// isn't set
+    ///
+    /// [`EMBED_COUNT_LIMIT`]: twilight_validate::message::EMBED_COUNT_LIMIT
+    pub fn handle_interaction(mut self, placement: Placement) -> Result<Self, Error> {
+        let Some(interaction) = self.interaction_info.interaction else {
+            return Ok(self);
+        };
+
+        let name = interaction
+            .member
+            .as_ref()
+            .and_then(|member| member.nick.as_ref())
+            .unwrap_or(&interaction.user.name);
+        let command = if self.interaction_info.is_chat_input {
+            format!("/{}", interaction.name)
+        } else {
+            interaction.name.clone()
+        };
+
+        match placement {
+            Placement::Embed => {
+                let mut avatar_info = avatar::Info {
+                    url: None,
+                    user_id: interaction.user.id,
+                    guild_id: self.source_guild_id,
+                    user_discriminator: interaction.user.discriminator,
+                    user_avatar: interaction.user.avatar,
+                    member_avatar: interaction.member.as_ref().and_then(|member| member.avatar),
+                };
+                avatar_info.set_url();
+
+                self.embeds.insert(
+                    0,
+                    Embed {
+                        title: None,
+                        author: Some(EmbedAuthor {
+                            icon_url: avatar_info.url,
+                            name: format!("{name} used {command}"),
+                            proxy_icon_url: None,
+                            url: None,
+                        }),
+                        color: None,
+                        description: None,
+                        fields: vec![],
+                        footer: None,
+                        image: None,
+                        kind: String::new(),
+                        provider: None,
+                        thumbnail: None,
+                        timestamp: None,
+                        url: None,
+                        video: None,
+                    },
+                );
+
+                if let Err(err) = twilight_validate::message::embeds(&self.embeds) {
+                    self.embeds.remove(0);
+                    return Err(err.into());
+                }
+            }
+            Placement::ContentPrefix => {
+                let header = format!("*{name} used `{command}`*\n");
+
+                if !self.options.split_content
+                    && header
+                        .chars()
+                        .count()
+                        .saturating_add(self.content.chars().count())
+                        > MESSAGE_CONTENT_LENGTH_MAX
+                {
+                    return Err(Error::ContentInvalid);
+                }
+
+                self.content.insert_str(0, &header);
+            }
+        }
+
+        Ok(self)
+    }
+}
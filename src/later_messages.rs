@@ -1,11 +1,29 @@
 //! Handling the message not being the last one in the channel
 
-use twilight_model::channel::Message;
+use std::time::Duration;
+
 #[cfg(doc)]
 use twilight_model::guild::Permissions;
-use twilight_validate::message::MESSAGE_CONTENT_LENGTH_MAX;
+use twilight_model::{
+    channel::{
+        message::{MessageFlags, MessageType},
+        Message,
+    },
+    id::{marker::MessageMarker, Id},
+    util::Timestamp,
+};
+use twilight_validate::{
+    embed::EMBED_TOTAL_LENGTH,
+    message::{EMBED_COUNT_LIMIT, MESSAGE_CONTENT_LENGTH_MAX, STICKER_MAX},
+};
+
+use crate::{error::Error, split, thread, MessageSource};
+
+/// Maximum number of attachments in a message
+pub const ATTACHMENT_COUNT_MAX: usize = 10;
 
-use crate::{error::Error, thread, MessageSource};
+/// Maximum seconds between two messages for Discord to group them visually
+const MESSAGE_GROUP_GAP_SECS: u64 = 7 * 60;
 
 /// Info about the later messages in the channel
 #[derive(Debug, Clone, PartialEq)]
@@ -18,11 +36,121 @@ pub struct Info {
     pub is_complete: bool,
     /// Whether [`MessageSource::create`] was called
     pub is_source_created: bool,
-    /// Whether [`MessageSource::later_messages`] or
-    /// [`MessageSource::later_messages_batched`] was called
+    /// Number of messages created in [`MessageSource::create`]
+    ///
+    /// This is more than 1 if the content was split, 0 is treated as 1 when
+    /// `is_source_created` is set
+    pub created_message_count: usize,
+    /// Where the later messages end, set with
+    /// [`MessageSource::later_messages_until`]
+    pub until: Until,
+    /// ID of the last message in the channel when
+    /// [`MessageSource::earlier_messages`] was called
+    ///
+    /// Messages after it are left out, since they may be the cloned earlier
+    /// messages
+    pub last_message_id: Option<Id<MessageMarker>>,
+    /// Whether [`MessageSource::later_messages`],
+    /// [`MessageSource::later_messages_batched`] or
+    /// [`MessageSource::later_messages_batched_with`] was called
     pub is_later_message_sources_created: bool,
 }
 
+/// Where the later messages end
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Until {
+    /// Until the last message in the channel
+    #[default]
+    End,
+    /// Until the message with the given ID, including it
+    MessageInclusive(Id<MessageMarker>),
+    /// Until the message with the given ID, excluding it
+    MessageExclusive(Id<MessageMarker>),
+    /// Until the last message sent at or before the given time
+    Timestamp(Timestamp),
+    /// Until the given number of messages
+    Count(usize),
+}
+
+impl Until {
+    /// Remove the messages after the end, return whether the end is reached
+    fn truncate(self, messages: &mut Vec<Message>) -> bool {
+        let end = match self {
+            Self::End => return false,
+            Self::MessageInclusive(message_id) => {
+                if messages
+                    .last()
+                    .is_some_and(|message| message.id == message_id)
+                {
+                    return true;
+                }
+                messages.iter().position(|message| message.id > message_id)
+            }
+            Self::MessageExclusive(message_id) => {
+                messages.iter().position(|message| message.id >= message_id)
+            }
+            Self::Timestamp(timestamp) => messages
+                .iter()
+                .position(|message| message.timestamp.as_micros() > timestamp.as_micros()),
+            Self::Count(count) => (messages.len() >= count).then_some(count),
+        };
+
+        end.is_some_and(|end_inner| {
+            messages.truncate(end_inner);
+            true
+        })
+    }
+}
+
+/// Rules for combining messages in
+/// [`MessageSource::later_messages_batched_with`]
+///
+/// The default rules match how Discord groups messages visually
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchRules {
+    /// Maximum time between two messages to combine them, `None` for no
+    /// limit
+    ///
+    /// Defaults to 7 minutes
+    pub max_gap: Option<Duration>,
+    /// Maximum number of messages to combine into one message, `None` for no
+    /// limit
+    ///
+    /// Defaults to `None`
+    pub max_count: Option<usize>,
+}
+
+impl Default for BatchRules {
+    fn default() -> Self {
+        Self {
+            max_gap: Some(Duration::from_secs(MESSAGE_GROUP_GAP_SECS)),
+            max_count: None,
+        }
+    }
+}
+
+impl BatchRules {
+    /// Return whether the rules allow combining the message into the batch,
+    /// whose last message was sent at `last_timestamp` and which has
+    /// `batch_count` messages
+    fn allow(self, last_timestamp: Timestamp, batch_count: usize, message: &Message) -> bool {
+        let gap_micros = message
+            .timestamp
+            .as_micros()
+            .saturating_sub(last_timestamp.as_micros());
+
+        let is_gap_over_max = self.max_gap.is_some_and(|max_gap| {
+            i64::try_from(max_gap.as_micros())
+                .is_ok_and(|max_gap_micros| gap_micros > max_gap_micros)
+        });
+        let is_count_at_max = self
+            .max_count
+            .is_some_and(|max_count| batch_count >= max_count);
+
+        !is_gap_over_max && !is_count_at_max
+    }
+}
+
 impl<'a> MessageSource<'a> {
     /// Check if this is in the last `n` messages in the channel, return
     /// [`Error::SourceAboveLimit`] if not
@@ -54,6 +182,24 @@ impl<'a> MessageSource<'a> {
         Ok(())
     }
 
+    /// Set where the messages sent after this end
+    ///
+    /// This is respected by [`MessageSource::check_is_in_last`],
+    /// [`MessageSource::later_messages`],
+    /// [`MessageSource::later_messages_batched`] and [`MessageSource::delete`],
+    /// so that only a range of messages is cloned or deleted without fetching
+    /// the rest of the channel
+    ///
+    /// # Warnings
+    ///
+    /// Should be called before the methods above, messages that were already
+    /// fetched are only removed when more messages are fetched
+    #[must_use]
+    pub const fn later_messages_until(mut self, until: Until) -> Self {
+        self.later_messages.until = until;
+        self
+    }
+
     /// Return [`MessageSource`] for messages sent after this
     ///
     /// Make sure the bot has these additional permissions
@@ -78,7 +224,7 @@ impl<'a> MessageSource<'a> {
     /// # Errors
     ///
     /// The vector element will be an error if the message can't be resent (See
-    /// [`MessageSource::from_message`])
+    /// [`MessageSource::from_message_with`], the options of `self` are used)
     ///
     /// Returns [`Error::Http`] if getting channel messages fails
     ///
@@ -93,8 +239,41 @@ impl<'a> MessageSource<'a> {
     /// Return [`MessageSource`] for messages sent after this after combining
     /// messages from the same author to the same message
     ///
-    /// This combines the messages' content separated with a newline, it's
-    /// provided to reduce the number of webhook executions
+    /// This is [`MessageSource::later_messages_batched_with`] with the default
+    /// [`BatchRules`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Http`] if getting channel messages fails
+    ///
+    /// Returns [`Error::DeserializeBody`] if deserializing channel messages
+    /// fails
+    pub async fn later_messages_batched(
+        &mut self,
+    ) -> Result<Vec<Result<MessageSource<'_>, Error>>, Error> {
+        self.later_messages_batched_with(BatchRules::default())
+            .await
+    }
+
+    /// Return [`MessageSource`] for messages sent after this after combining
+    /// messages from the same author to the same message with the given
+    /// [`BatchRules`]
+    ///
+    /// This combines the messages' content separated with a newline and their
+    /// attachments, embeds and stickers, it's provided to reduce the number of
+    /// webhook executions
+    ///
+    /// The content is combined so that it renders the same as the separate
+    /// messages, code blocks left open are closed, multi-line block quotes are
+    /// turned into single-line block quotes and lists are separated with an
+    /// empty line
+    ///
+    /// A message is put in a new batch instead if combining it would exceed
+    /// [`MESSAGE_CONTENT_LENGTH_MAX`] characters, [`ATTACHMENT_COUNT_MAX`]
+    /// attachments, [`EMBED_COUNT_LIMIT`] embeds, [`EMBED_TOTAL_LENGTH`]
+    /// characters in embeds or [`STICKER_MAX`] stickers, or if `rules` don't
+    /// allow it, replies, messages with threads and voice messages are never
+    /// combined
     ///
     /// See [`MessageSource::later_messages`] for more
     ///
@@ -108,41 +287,43 @@ impl<'a> MessageSource<'a> {
     ///
     /// Returns [`Error::DeserializeBody`] if deserializing channel messages
     /// fails
-    pub async fn later_messages_batched(
+    pub async fn later_messages_batched_with(
         &mut self,
+        rules: BatchRules,
     ) -> Result<Vec<Result<MessageSource<'_>, Error>>, Error> {
         self.set_later_messages(None).await?;
 
         // clone to another vec because removing elements from the vec is more expensive
-        let mut messages_batched = vec![];
+        let mut messages_batched: Vec<Message> = vec![];
+        let mut last_timestamp = None;
+        let mut batch_count = 0_usize;
 
         for message in self.later_messages.messages.clone() {
-            let Some(last_message) = messages_batched.last_mut() else {
-                messages_batched.push(message);
-                continue;
+            let timestamp = message.timestamp;
+
+            let is_batched = match (messages_batched.last_mut(), last_timestamp) {
+                (Some(last_message), Some(last_timestamp_inner)) => {
+                    rules.allow(last_timestamp_inner, batch_count, &message)
+                        && batch(last_message, &message)
+                }
+                _ => false,
             };
 
-            if last_message.author.id == message.author.id
-                && last_message
-                    .content
-                    .chars()
-                    .count()
-                    .saturating_add(message.content.chars().count())
-                    < MESSAGE_CONTENT_LENGTH_MAX
-            // not <= because we push '\n' too
-            {
-                last_message.content.push('\n');
-                last_message.content.push_str(&message.content);
+            if is_batched {
+                batch_count = batch_count.saturating_add(1);
             } else {
                 messages_batched.push(message);
+                batch_count = 1;
             }
+
+            last_timestamp = Some(timestamp);
         }
         self.later_messages.messages = messages_batched;
 
         Ok(self.later_message_sources())
     }
 
-    async fn set_later_messages(&mut self, limit: Option<u16>) -> Result<(), Error> {
+    pub(crate) async fn set_later_messages(&mut self, limit: Option<u16>) -> Result<(), Error> {
         loop {
             if let Some(limit_inner) = limit {
                 if self.later_messages.messages.len() >= usize::from(limit_inner) {
@@ -168,7 +349,7 @@ impl<'a> MessageSource<'a> {
                 .await?;
 
             for message in &mut message_batch {
-                message.guild_id = Some(self.guild_id);
+                message.guild_id = self.source_guild_id;
             }
 
             self.later_messages.is_complete =
@@ -177,36 +358,141 @@ impl<'a> MessageSource<'a> {
             self.later_messages.messages.extend(
                 message_batch
                     .into_iter()
-                    // skip message sent in self.create
-                    .skip(usize::from(
-                        self.later_messages.is_source_created
+                    // skip messages sent in self.create
+                    .skip(
+                        if self.later_messages.is_source_created
                             && self.source_thread_id == self.thread_info.id()
                             && self.channel_id == self.source_channel_id
-                            && self.later_messages.messages.is_empty(),
-                    ))
+                            && self.later_messages.messages.is_empty()
+                        {
+                            // 0 if `is_source_created` was set manually
+                            self.later_messages.created_message_count.max(1)
+                        } else {
+                            0
+                        },
+                    )
                     .rev()
                     // skip the system message when used in threads
                     .skip(usize::from(self.thread_info.id().is_some())),
             );
+
+            let is_until_reached = self
+                .later_messages
+                .until
+                .truncate(&mut self.later_messages.messages);
+            let is_last_message_reached =
+                self.later_messages
+                    .last_message_id
+                    .is_some_and(|last_message_id| {
+                        Until::MessageInclusive(last_message_id)
+                            .truncate(&mut self.later_messages.messages)
+                    });
+            if is_until_reached || is_last_message_reached {
+                self.later_messages.is_complete = true;
+            }
         }
     }
 
     fn later_message_sources(&mut self) -> Vec<Result<MessageSource<'_>, Error>> {
         self.later_messages.is_later_message_sources_created = true;
 
-        self.later_messages
-            .messages
+        self.message_sources(&self.later_messages.messages)
+    }
+
+    /// Return [`MessageSource`] for the given messages in the same channel,
+    /// using the options of `self`
+    pub(crate) fn message_sources<'s>(
+        &'s self,
+        messages: &'s [Message],
+    ) -> Vec<Result<MessageSource<'s>, Error>> {
+        messages
             .iter()
             .map(|message| {
-                MessageSource::from_message(message, self.http).map(|mut source| {
+                MessageSource::new(
+                    message,
+                    self.source_guild_id,
+                    self.guild_id,
+                    self.options,
+                    self.http,
+                )
+                .map(|mut source| {
                     source.thread_info = self
                         .thread_info
                         .id()
                         .map_or(thread::Info::NotIn, thread::Info::In);
                     source.channel_id = self.channel_id;
+                    #[cfg(feature = "upload")]
+                    {
+                        source.download_options = self.download_options.clone();
+                        source.guild_premium_tier = self.guild_premium_tier;
+                        source.attachment_sticker_info.upload_size_limit =
+                            self.attachment_sticker_info.upload_size_limit;
+                    }
                     source
                 })
             })
             .collect()
     }
 }
+
+/// Combine the message into the last message in the batch, return whether it
+/// was combined
+///
+/// The message isn't combined if it would exceed the message limits or lose
+/// its reply or thread
+fn batch(last_message: &mut Message, message: &Message) -> bool {
+    let is_voice = |message_inner: &Message| {
+        message_inner
+            .flags
+            .is_some_and(|flags| flags.contains(MessageFlags::IS_VOICE_MESSAGE))
+    };
+    let embeds_length = |message_inner: &Message| {
+        message_inner
+            .embeds
+            .iter()
+            .map(twilight_validate::embed::chars)
+            .fold(0, usize::saturating_add)
+    };
+
+    if last_message.author.id != message.author.id
+        || message.kind == MessageType::Reply
+        || last_message.thread.is_some()
+        || message.thread.is_some()
+        || is_voice(last_message)
+        || is_voice(message)
+        || last_message
+            .attachments
+            .len()
+            .saturating_add(message.attachments.len())
+            > ATTACHMENT_COUNT_MAX
+        || last_message
+            .embeds
+            .len()
+            .saturating_add(message.embeds.len())
+            > EMBED_COUNT_LIMIT
+        || embeds_length(last_message).saturating_add(embeds_length(message)) > EMBED_TOTAL_LENGTH
+        || last_message
+            .sticker_items
+            .len()
+            .saturating_add(message.sticker_items.len())
+            > STICKER_MAX
+    {
+        return false;
+    }
+
+    let content = split::join(&last_message.content, &message.content);
+    if content.chars().count() > MESSAGE_CONTENT_LENGTH_MAX {
+        return false;
+    }
+
+    last_message.content = content;
+    last_message
+        .attachments
+        .extend(message.attachments.iter().cloned());
+    last_message.embeds.extend(message.embeds.iter().cloned());
+    last_message
+        .sticker_items
+        .extend(message.sticker_items.iter().cloned());
+
+    true
+}
@@ -97,9 +97,11 @@ use anyhow as _;
 use dotenvy as _;
 #[cfg(test)]
 use tokio as _;
-use twilight_http::{request::channel::webhook::ExecuteWebhookAndWait, Client};
+use twilight_http::{request::channel::webhook::ExecuteWebhookAndWait, Client, Response};
 #[cfg(doc)]
 use twilight_model::guild::Permissions;
+#[cfg(feature = "upload")]
+use twilight_model::guild::PremiumTier;
 use twilight_model::{
     channel::{
         message::{Embed, MessageFlags},
@@ -116,13 +118,21 @@ use crate::error::Error;
 pub mod attachment_sticker;
 pub mod avatar;
 pub mod component;
-mod constructor;
+pub mod constructor;
 mod delete;
+pub mod earlier_messages;
 pub mod error;
+pub mod interaction;
 pub mod later_messages;
+pub mod link_refresh;
+#[cfg(feature = "lottie")]
+mod lottie;
 pub mod reaction;
 pub mod reference;
 pub mod response;
+mod rich_presence;
+mod split;
+mod system;
 pub mod thread;
 mod username;
 
@@ -168,12 +178,21 @@ pub struct MessageSource<'a> {
     ///
     /// If the message is in a thread, this should be the parent thread's ID
     pub channel_id: Id<ChannelMarker>,
+    /// ID of the guild the source message is in
+    ///
+    /// `None` if the source message is in a DM or group DM
+    pub source_guild_id: Option<Id<GuildMarker>>,
     /// ID of the guild the message is in
     pub guild_id: Id<GuildMarker>,
     /// Emoji IDs of the guild the message is in
     ///
     /// `None` if it has never been needed
     pub guild_emoji_ids: Option<Vec<Id<EmojiMarker>>>,
+    /// Premium tier of the guild the message is in
+    ///
+    /// `None` if it has never been needed
+    #[cfg(feature = "upload")]
+    pub guild_premium_tier: Option<PremiumTier>,
     /// Username of the message's author
     pub username: String,
     /// Name to be used for the webhook that will be used to create the message
@@ -186,10 +205,22 @@ pub struct MessageSource<'a> {
     pub reaction_info: reaction::Info<'a>,
     /// Info about the message's attachments
     pub attachment_sticker_info: attachment_sticker::Info<'a>,
+    /// Options for downloading the attachments to re-upload
+    #[cfg(feature = "upload")]
+    pub download_options: attachment_sticker::DownloadOptions,
     /// Info about the message's components
     pub component_info: component::Info,
+    /// Info about the interaction the message is a response to
+    pub interaction_info: interaction::Info<'a>,
     /// Info about the message's thread
     pub thread_info: thread::Info,
+    /// State of the thread to restore after the message is created
+    ///
+    /// `None` if the thread wasn't archived or locked, or
+    /// [`MessageSource::handle_thread_archived`] wasn't called
+    pub thread_archived: Option<thread::ArchivedState>,
+    /// Messages sent before the source
+    pub earlier_messages: earlier_messages::Info,
     /// Messages sent after the source
     pub later_messages: later_messages::Info,
     /// Webhook ID and token to execute to clone messages with
@@ -198,6 +229,8 @@ pub struct MessageSource<'a> {
     ///
     /// `None` if [`MessageSource::create`] wasn't called
     pub response: Option<response::MaybeDeserialized<Message>>,
+    /// Options the source was created with
+    pub options: constructor::Options,
     /// The client to use for requests
     pub http: &'a Client,
 }
@@ -218,6 +251,13 @@ impl<'a> MessageSource<'a> {
     /// beforehand, retries each execution up to 5 times, if all of these
     /// are rate-limited, returns the HTTP error
     ///
+    /// If [`constructor::Options::split_content`] is set and the content is
+    /// too long, executes the webhook once for each part of the content,
+    /// embeds and components are sent with the last part, attachments are sent
+    /// with the first part if the message is a post and with the last part
+    /// otherwise, [`MessageSource::response`] is the response of the last
+    /// part
+    ///
     /// # Warnings
     ///
     /// Other methods on [`MessageSource`] are provided to handle edge-cases,
@@ -230,7 +270,8 @@ impl<'a> MessageSource<'a> {
     /// # Errors
     ///
     /// Returns [`Error::Http`] if getting, creating or executing the webhook
-    /// fails
+    /// fails, or restoring the thread's archived state fails, the thread's
+    /// archived state is restored even if creating the message fails
     ///
     /// Returns [`Error::DeserializeBody`] if deserializing the webhook
     ///
@@ -238,37 +279,78 @@ impl<'a> MessageSource<'a> {
     ///
     /// Returns [`Error::MessageValidation`] if the given message is invalid,
     /// shouldn't happen unless the message was mutated
+    ///
+    /// Returns [`Error::MediaPostAttachment`] if the message is a media channel
+    /// post without attachments to upload
+    #[cfg_attr(
+        feature = "upload",
+        doc = "",
+        doc = " Returns the error of",
+        doc = " [`AttachmentFetcher::fetch`](attachment_sticker::AttachmentFetcher::fetch)",
+        doc = " if downloading deferred attachments fails"
+    )]
     pub async fn create(mut self) -> Result<MessageSource<'a>, Error> {
+        // restore the thread's state even if creating the message fails
+        let create_result = self.create_messages().await;
+        let restore_result = self.restore_thread_archived().await;
+        create_result?;
+        restore_result?;
+
+        Ok(self)
+    }
+
+    /// Create the messages in [`MessageSource::create`], without restoring the
+    /// thread's archived state
+    async fn create_messages(&mut self) -> Result<(), Error> {
+        self.check_media_post()?;
         self.set_webhook().await?;
         self.avatar_info.set_url();
+        #[cfg(feature = "upload")]
+        self.download_deferred_attachments().await?;
 
-        for i in 0..=5_u8 {
-            match self.webhook_exec()?.await {
-                Ok(response) => {
-                    self.response = Some(response::MaybeDeserialized::Response(response));
-                    break;
-                }
-                Err(err)
-                    if matches!(
-                        err.kind(),
-                        twilight_http::error::ErrorType::Response {
-                            error: twilight_http::api_error::ApiError::Ratelimited(_),
-                            ..
-                        }
-                    ) =>
-                {
-                    if i == 5 {
-                        return Err(Error::Http(err));
-                    }
-                    continue;
-                }
-                Err(err) => return Err(Error::Http(err)),
+        let mut content_parts = if self.options.split_content {
+            split::parts(&self.content)
+        } else {
+            vec![]
+        };
+        let content_last = content_parts.pop();
+
+        let mut post_id = None;
+        for content_part in &content_parts {
+            let exec = Exec {
+                content: content_part,
+                is_last: false,
+                post_id,
+            };
+            let response = self.webhook_exec_retry(&exec).await?;
+
+            if post_id.is_none()
+                && matches!(
+                    self.thread_info,
+                    thread::Info::CreatedPost(_) | thread::Info::CreatedMediaPost(_)
+                )
+            {
+                post_id = Some(response.model().await?.channel_id);
+                #[cfg(feature = "upload")]
+                self.release_attachments();
             }
         }
 
+        let exec = Exec {
+            content: content_last.as_deref().unwrap_or(&self.content),
+            is_last: true,
+            post_id,
+        };
+        self.response = Some(response::MaybeDeserialized::Response(
+            self.webhook_exec_retry(&exec).await?,
+        ));
+        #[cfg(feature = "upload")]
+        self.release_attachments();
+
+        self.later_messages.created_message_count = content_parts.len().saturating_add(1);
         self.later_messages.is_source_created = true;
 
-        Ok(self)
+        Ok(())
     }
 
     /// Set the name of the webhook to use for creating messages
@@ -309,26 +391,59 @@ impl<'a> MessageSource<'a> {
         Ok(())
     }
 
-    fn webhook_exec(&self) -> Result<ExecuteWebhookAndWait<'_>, Error> {
+    async fn webhook_exec_retry(&self, exec: &Exec<'_>) -> Result<Response<Message>, Error> {
+        let mut i = 0_u8;
+        loop {
+            match self.webhook_exec(exec)?.await {
+                Ok(response) => return Ok(response),
+                Err(err)
+                    if i < 5
+                        && matches!(
+                            err.kind(),
+                            twilight_http::error::ErrorType::Response {
+                                error: twilight_http::api_error::ApiError::Ratelimited(_),
+                                ..
+                            }
+                        ) =>
+                {
+                    i = i.saturating_add(1);
+                }
+                Err(err) => return Err(Error::Http(err)),
+            }
+        }
+    }
+
+    fn webhook_exec<'b>(&'b self, exec: &Exec<'b>) -> Result<ExecuteWebhookAndWait<'b>, Error> {
         let (webhook_id, webhook_token) = self.webhook.as_ref().unwrap();
 
         let mut execute_webhook = self
             .http
             .execute_webhook(*webhook_id, webhook_token)
-            .content(&self.content)?
-            .embeds(&self.embeds)?
-            .components(&self.component_info.url_components)?
+            .content(exec.content)?
             .username(&self.username)?
             .avatar_url(self.avatar_info.url.as_ref().unwrap())
             .tts(self.tts);
 
+        if exec.is_last {
+            execute_webhook = execute_webhook
+                .embeds(&self.embeds)?
+                .components(&self.component_info.url_components)?;
+        }
+
         match &self.thread_info {
+            _ if exec.post_id.is_some() => {}
             thread::Info::In(thread_id) => execute_webhook = execute_webhook.thread_id(*thread_id),
-            thread::Info::CreatedPost(channel) => {
+            thread::Info::InPrivate(thread) => {
+                execute_webhook = execute_webhook.thread_id(thread.id);
+            }
+            thread::Info::CreatedPost(channel) | thread::Info::CreatedMediaPost(channel) => {
                 execute_webhook = execute_webhook.thread_name(channel.name.as_ref().unwrap());
             }
             _ => {}
         }
+        if let Some(post_id) = exec.post_id {
+            execute_webhook = execute_webhook.thread_id(post_id);
+        }
 
         if let Some(flags) = self.flags {
             execute_webhook = execute_webhook.flags(flags);
@@ -336,8 +451,21 @@ impl<'a> MessageSource<'a> {
 
         #[cfg(feature = "upload")]
         {
-            execute_webhook =
-                execute_webhook.attachments(&self.attachment_sticker_info.attachments_upload)?;
+            // posts are created with the first message, media posts require attachments,
+            // the post ID is only unknown in the first execution
+            let is_attachment_exec = if matches!(
+                self.thread_info,
+                thread::Info::CreatedPost(_) | thread::Info::CreatedMediaPost(_)
+            ) {
+                exec.post_id.is_none()
+            } else {
+                exec.is_last
+            };
+
+            if is_attachment_exec {
+                execute_webhook = execute_webhook
+                    .attachments(&self.attachment_sticker_info.attachments_upload)?;
+            }
         }
 
         // not waiting causes race condition issues in the client
@@ -362,4 +490,35 @@ impl<'a> MessageSource<'a> {
 
         Ok(())
     }
+
+    #[cfg(feature = "upload")]
+    async fn set_guild_premium_tier(&mut self) -> Result<(), Error> {
+        if self.guild_premium_tier.is_some() {
+            return Ok(());
+        }
+
+        self.guild_premium_tier = Some(
+            self.http
+                .guild(self.guild_id)
+                .await?
+                .model()
+                .await?
+                .premium_tier,
+        );
+
+        Ok(())
+    }
+}
+
+/// A single webhook execution in [`MessageSource::create`]
+///
+/// There are multiple executions when the content is split
+struct Exec<'a> {
+    /// Content of this execution
+    content: &'a str,
+    /// Whether this is the last execution, embeds and components are only
+    /// sent in the last execution
+    is_last: bool,
+    /// ID of the post created in the first execution
+    post_id: Option<Id<ChannelMarker>>,
 }
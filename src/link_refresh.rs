@@ -0,0 +1,259 @@
+//! Refreshing expiring attachment links
+//!
+//! Discord signs attachment links, they expire some time after they're
+//! fetched, even if the message they belong to isn't deleted
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use twilight_http::{
+    request::{Method, RequestBuilder},
+    routing::Path,
+    Client,
+};
+use twilight_model::channel::{message::Embed, Message};
+
+use crate::{error::Error, MessageSource};
+
+/// Maximum number of links the refresh endpoint accepts at once
+const REFRESH_URLS_MAX: usize = 50;
+
+/// Prefixes of attachment links
+const ATTACHMENT_URL_PREFIXES: [&str; 2] = [
+    "https://cdn.discordapp.com/attachments/",
+    "https://media.discordapp.net/attachments/",
+];
+
+#[derive(Serialize)]
+struct RefreshUrls<'a> {
+    attachment_urls: &'a [&'a str],
+}
+
+#[derive(Deserialize)]
+struct RefreshedUrls {
+    refreshed_urls: Vec<RefreshedUrl>,
+}
+
+#[derive(Deserialize)]
+struct RefreshedUrl {
+    original: String,
+    refreshed: String,
+}
+
+impl MessageSource<'_> {
+    /// Refresh the links to the attachments
+    ///
+    /// The refreshed links are then used in
+    /// [`MessageSource::handle_attachment_link`],
+    /// [`MessageSource::handle_attachment_embed`] and
+    /// `MessageSource::handle_attachment_upload_or_link`, so that they don't
+    /// expire right after the message is cloned
+    ///
+    /// # Warnings
+    ///
+    /// The refreshed links still expire, see [`relink_expiring_attachments`] to
+    /// refresh them in the cloned message
+    ///
+    /// See [`refresh_urls`] for how this is rate-limited
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Http`] if refreshing the links fails
+    ///
+    /// Returns [`Error::DeserializeBody`] if deserializing the refreshed links
+    /// fails
+    pub async fn refresh_attachment_urls(mut self) -> Result<Self, Error> {
+        let urls = self
+            .attachment_sticker_info
+            .attachments
+            .iter()
+            .map(|attachment| attachment.url.as_str())
+            .collect::<Vec<_>>();
+
+        self.attachment_sticker_info.refreshed_urls = Some(refresh_urls(self.http, &urls).await?);
+
+        Ok(self)
+    }
+}
+
+/// Return refreshed versions of the given attachment links in the same order
+///
+/// # Warnings
+///
+/// twilight doesn't know this route, so its rate-limits are tracked in the
+/// bucket of getting Nitro sticker packs, being rate-limited on one of these
+/// delays the other
+///
+/// # Errors
+///
+/// Returns [`Error::Http`] if refreshing the links fails
+///
+/// Returns [`Error::DeserializeBody`] if deserializing the refreshed links
+/// fails
+pub async fn refresh_urls(http: &Client, urls: &[&str]) -> Result<Vec<String>, Error> {
+    let mut refreshed_urls = Vec::with_capacity(urls.len());
+
+    for urls_chunk in urls.chunks(REFRESH_URLS_MAX) {
+        // twilight doesn't have this route, so its ratelimits are tracked with a
+        // similarly global route, see the warning above
+        let request = RequestBuilder::raw(
+            Method::Post,
+            Path::StickerPacks,
+            "attachments/refresh-urls".to_owned(),
+        )
+        .json(&RefreshUrls {
+            attachment_urls: urls_chunk,
+        })?
+        .build();
+
+        let refreshed = http
+            .request::<RefreshedUrls>(request)
+            .await?
+            .model()
+            .await?
+            .refreshed_urls;
+
+        refreshed_urls.extend(urls_chunk.iter().map(|url| {
+            refreshed
+                .iter()
+                .find(|refreshed_url| refreshed_url.original == *url)
+                .map_or_else(
+                    || (*url).to_owned(),
+                    |refreshed_url| refreshed_url.refreshed.clone(),
+                )
+        }));
+    }
+
+    Ok(refreshed_urls)
+}
+
+/// Return when the given attachment link expires
+///
+/// `None` if the link isn't signed or the expiry time is invalid
+#[must_use]
+pub fn url_expires_at(url: &str) -> Option<SystemTime> {
+    let (_, query) = url.split_once('?')?;
+    let expires_at = query
+        .split('&')
+        .find_map(|param| param.strip_prefix("ex="))?;
+
+    UNIX_EPOCH.checked_add(Duration::from_secs(
+        u64::from_str_radix(expires_at, 16).ok()?,
+    ))
+}
+
+/// Refresh the attachment links in a cloned message that expire in the given
+/// duration
+///
+/// This edits the content and embeds of the message created by the webhook
+/// to use the refreshed links, call this periodically, such as every day, on
+/// messages cloned with [`MessageSource::handle_attachment_link`] or
+/// [`MessageSource::handle_attachment_embed`]
+///
+/// Re-uploaded attachments belong to the cloned message, so Discord signs
+/// their links again when the message is fetched and they don't need to be
+/// refreshed
+///
+/// Returns whether the message was edited, it's not edited if no links expire
+/// in the given duration, or if the message wasn't created by a webhook whose
+/// token the bot can see, such as a channel follower webhook
+///
+/// # Errors
+///
+/// Returns [`Error::Http`] if refreshing the links, getting the webhook or
+/// editing the message fails
+///
+/// Returns [`Error::DeserializeBody`] if deserializing the refreshed links or
+/// the webhook fails
+///
+/// Returns [`Error::MessageValidation`] if the message is invalid after
+/// refreshing the links, shouldn't happen unless the links got longer
+pub async fn relink_expiring_attachments(
+    http: &Client,
+    message: &Message,
+    expires_in: Duration,
+) -> Result<bool, Error> {
+    let Some(webhook_id) = message.webhook_id else {
+        return Ok(false);
+    };
+
+    let now = SystemTime::now();
+    let mut urls = attachment_urls(&message.content)
+        .chain(message.embeds.iter().flat_map(|embed| {
+            embed.image.iter().map(|image| image.url.as_str()).chain(
+                embed
+                    .description
+                    .iter()
+                    .flat_map(|description| attachment_urls(description)),
+            )
+        }))
+        .filter(|url| {
+            url_expires_at(url).is_some_and(|expires_at| {
+                expires_at
+                    .duration_since(now)
+                    .map_or(true, |expires_after| expires_after < expires_in)
+            })
+        })
+        .collect::<Vec<_>>();
+    urls.sort_unstable();
+    urls.dedup();
+
+    if urls.is_empty() {
+        return Ok(false);
+    }
+
+    let webhook = http.webhook(webhook_id).await?.model().await?;
+    let Some(token) = webhook.token else {
+        return Ok(false);
+    };
+
+    let refreshed_urls = refresh_urls(http, &urls).await?;
+    let relink = |text: &str| {
+        urls.iter()
+            .zip(&refreshed_urls)
+            .fold(text.to_owned(), |relinked, (url, refreshed_url)| {
+                relinked.replace(url, refreshed_url)
+            })
+    };
+
+    let content = relink(&message.content);
+    let embeds = message
+        .embeds
+        .iter()
+        .map(|embed| {
+            let mut embed_relinked = embed.clone();
+            if let Some(image) = &mut embed_relinked.image {
+                image.url = relink(&image.url);
+                image.proxy_url = None;
+            }
+            embed_relinked.description = embed.description.as_deref().map(relink);
+            embed_relinked
+        })
+        .collect::<Vec<Embed>>();
+
+    let mut update_message = http
+        .update_webhook_message(webhook_id, &token, message.id)
+        .content(Some(&content))?
+        .embeds(Some(&embeds))?;
+    // the message is in a thread if it's not in the webhook's channel
+    if webhook.channel_id != message.channel_id {
+        update_message = update_message.thread_id(message.channel_id);
+    }
+    update_message.await?;
+
+    Ok(true)
+}
+
+/// Return the attachment links in the text
+fn attachment_urls(text: &str) -> impl Iterator<Item = &str> {
+    ATTACHMENT_URL_PREFIXES.iter().flat_map(move |prefix| {
+        text.match_indices(prefix).filter_map(move |(start, _)| {
+            let url = text.get(start..)?;
+            let end = url
+                .find(|c: char| c.is_whitespace() || matches!(c, ')' | '>' | '|'))
+                .unwrap_or(url.len());
+
+            url.get(..end)
+        })
+    })
+}
@@ -0,0 +1,645 @@
+//! Rendering Lottie stickers to GIFs
+//!
+//! This supports the subset of Lottie that stickers commonly use: shape
+//! layers with rectangles, ellipses, paths, solid fills and strokes, groups,
+//! transforms, parenting and keyframes with easing, anything else is skipped
+
+use gif::{DisposalMethod, Encoder, Frame, Repeat};
+use serde_json::Value;
+use tiny_skia::{
+    Color, FillRule, LineCap, LineJoin, Paint, Path, PathBuilder, Pixmap, Rect, Stroke, Transform,
+};
+
+use crate::error::Error;
+
+/// Width and height of the rendered GIF, the size stickers are shown in
+const SIZE: u16 = 160;
+/// Maximum frame rate of the rendered GIF
+const FRAME_RATE_MAX: f32 = 30.0;
+/// Maximum number of frames to render
+const FRAME_COUNT_MAX: u16 = 300;
+/// Maximum depth of layer parenting, to avoid cycles
+const PARENT_DEPTH_MAX: u8 = 16;
+/// Distance of bezier control points from the corners to draw a quarter circle
+const KAPPA: f32 = 0.552_284_8;
+
+/// Render the Lottie animation to an animated GIF
+///
+/// # Errors
+///
+/// Returns [`Error::LottieRender`] if the animation is invalid or encoding the
+/// GIF fails
+pub(crate) fn render_gif(json: &[u8]) -> Result<Vec<u8>, Error> {
+    let animation = serde_json::from_slice::<Value>(json).map_err(|_| Error::LottieRender)?;
+    let width = number(animation.get("w")).ok_or(Error::LottieRender)?;
+    let height = number(animation.get("h")).ok_or(Error::LottieRender)?;
+    let frame_rate = number(animation.get("fr"))
+        .filter(|frame_rate| *frame_rate > 0.0)
+        .ok_or(Error::LottieRender)?;
+    let in_point = number(animation.get("ip")).unwrap_or(0.0);
+    let out_point = number(animation.get("op")).ok_or(Error::LottieRender)?;
+    let layers = animation
+        .get("layers")
+        .and_then(Value::as_array)
+        .ok_or(Error::LottieRender)?;
+
+    let frame_step = (frame_rate / FRAME_RATE_MAX).ceil().max(1.0);
+    let size = f32::from(SIZE);
+    let scale = Transform::from_scale(size / width, size / height);
+
+    let mut gif = vec![];
+    {
+        let mut encoder =
+            Encoder::new(&mut gif, SIZE, SIZE, &[]).map_err(|_| Error::LottieRender)?;
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|_| Error::LottieRender)?;
+
+        let mut time = in_point;
+        for _ in 0..FRAME_COUNT_MAX {
+            if time >= out_point {
+                break;
+            }
+
+            let mut pixmap =
+                Pixmap::new(u32::from(SIZE), u32::from(SIZE)).ok_or(Error::LottieRender)?;
+            for layer in layers.iter().rev() {
+                render_layer(&mut pixmap, layers, layer, scale, time);
+            }
+
+            let mut rgba = pixmap
+                .pixels()
+                .iter()
+                .flat_map(|pixel| {
+                    let color = pixel.demultiply();
+                    // gif only supports fully transparent pixels
+                    if color.alpha() < 128 {
+                        [0, 0, 0, 0]
+                    } else {
+                        [color.red(), color.green(), color.blue(), 255]
+                    }
+                })
+                .collect::<Vec<_>>();
+            let mut frame = Frame::from_rgba_speed(SIZE, SIZE, &mut rgba, 10);
+            frame.delay = frame_delay(frame_step, frame_rate);
+            frame.dispose = DisposalMethod::Background;
+            encoder
+                .write_frame(&frame)
+                .map_err(|_| Error::LottieRender)?;
+
+            time += frame_step;
+        }
+    }
+
+    Ok(gif)
+}
+
+/// Return the delay of a frame in hundredths of a second
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn frame_delay(frame_step: f32, frame_rate: f32) -> u16 {
+    (frame_step / frame_rate * 100.0).round().clamp(2.0, 100.0) as u16
+}
+
+fn render_layer(pixmap: &mut Pixmap, layers: &[Value], layer: &Value, scale: Transform, time: f32) {
+    // only shape layers are drawn
+    if layer.get("ty").and_then(Value::as_u64) != Some(4)
+        || layer.get("hd").and_then(Value::as_bool) == Some(true)
+        || number(layer.get("ip")).is_some_and(|in_point| time < in_point)
+        || number(layer.get("op")).is_some_and(|out_point| time >= out_point)
+    {
+        return;
+    }
+    let Some(shapes) = layer.get("shapes").and_then(Value::as_array) else {
+        return;
+    };
+
+    let Some((transform, opacity)) = layer_transform(layers, layer, time, 0) else {
+        return;
+    };
+
+    render_group(pixmap, shapes, scale.pre_concat(transform), opacity, time);
+}
+
+/// Return the transform and opacity of the layer, including its parents'
+/// transforms
+fn layer_transform(
+    layers: &[Value],
+    layer: &Value,
+    time: f32,
+    depth: u8,
+) -> Option<(Transform, f32)> {
+    let (transform, opacity) = transform(layer.get("ks"), time);
+
+    let Some(parent_index) = layer.get("parent").and_then(Value::as_u64) else {
+        return Some((transform, opacity));
+    };
+    if depth >= PARENT_DEPTH_MAX {
+        return None;
+    }
+    let parent = layers
+        .iter()
+        .find(|other_layer| other_layer.get("ind").and_then(Value::as_u64) == Some(parent_index))?;
+    let (parent_transform, _) = layer_transform(layers, parent, time, depth.saturating_add(1))?;
+
+    // parents' opacity doesn't apply to children
+    Some((parent_transform.pre_concat(transform), opacity))
+}
+
+/// Draw the shapes in the group
+///
+/// Fills and strokes apply to the shapes listed before them, items listed
+/// first are drawn on top
+fn render_group(
+    pixmap: &mut Pixmap,
+    items: &[Value],
+    transform: Transform,
+    opacity: f32,
+    time: f32,
+) {
+    for (index, item) in items.iter().enumerate().rev() {
+        if item.get("hd").and_then(Value::as_bool) == Some(true) {
+            continue;
+        }
+
+        match item.get("ty").and_then(Value::as_str) {
+            Some("gr") => {
+                let group_items = item
+                    .get("it")
+                    .and_then(Value::as_array)
+                    .map_or(&[][..], Vec::as_slice);
+                let (group_transform, group_opacity) = group_transform(group_items, time);
+                render_group(
+                    pixmap,
+                    group_items,
+                    transform.pre_concat(group_transform),
+                    opacity * group_opacity,
+                    time,
+                );
+            }
+            Some(kind @ ("fl" | "st")) => {
+                let Some(paint) = paint(item, opacity, time) else {
+                    continue;
+                };
+                let shapes = items.get(..index).unwrap_or_default();
+
+                for path in paths(shapes, time) {
+                    if kind == "fl" {
+                        let fill_rule = if item.get("r").and_then(Value::as_u64) == Some(2) {
+                            FillRule::EvenOdd
+                        } else {
+                            FillRule::Winding
+                        };
+                        pixmap.fill_path(&path, &paint, fill_rule, transform, None);
+                    } else {
+                        let stroke = Stroke {
+                            width: value(item.get("w"), time)
+                                .and_then(|width| width.first().copied())
+                                .unwrap_or(1.0),
+                            line_cap: match item.get("lc").and_then(Value::as_u64) {
+                                Some(2) => LineCap::Round,
+                                Some(3) => LineCap::Square,
+                                _ => LineCap::Butt,
+                            },
+                            line_join: match item.get("lj").and_then(Value::as_u64) {
+                                Some(2) => LineJoin::Round,
+                                Some(3) => LineJoin::Bevel,
+                                _ => LineJoin::Miter,
+                            },
+                            ..Stroke::default()
+                        };
+                        pixmap.stroke_path(&path, &paint, &stroke, transform, None);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Return the paths of the shapes, including the ones in groups
+fn paths(items: &[Value], time: f32) -> Vec<Path> {
+    let mut paths = vec![];
+
+    for item in items {
+        if item.get("hd").and_then(Value::as_bool) == Some(true) {
+            continue;
+        }
+
+        match item.get("ty").and_then(Value::as_str) {
+            Some("gr") => {
+                let group_items = item
+                    .get("it")
+                    .and_then(Value::as_array)
+                    .map_or(&[][..], Vec::as_slice);
+                let (group_transform, _) = group_transform(group_items, time);
+                paths.extend(
+                    self::paths(group_items, time)
+                        .into_iter()
+                        .filter_map(|path| path.transform(group_transform)),
+                );
+            }
+            Some("rc") => paths.extend(rectangle(item, time)),
+            Some("el") => paths.extend(ellipse(item, time)),
+            Some("sh") => paths.extend(bezier(item.get("ks"), time)),
+            _ => {}
+        }
+    }
+
+    paths
+}
+
+fn rectangle(item: &Value, time: f32) -> Option<Path> {
+    let [x, y] = vector(item.get("p"), time)?;
+    let [width, height] = vector(item.get("s"), time)?;
+    let radius = value(item.get("r"), time)
+        .and_then(|radius| radius.first().copied())
+        .unwrap_or(0.0)
+        .min(width / 2.0)
+        .min(height / 2.0);
+
+    let left = x - width / 2.0;
+    let top = y - height / 2.0;
+    if radius <= 0.0 {
+        return Some(PathBuilder::from_rect(Rect::from_xywh(
+            left, top, width, height,
+        )?));
+    }
+
+    let right = x + width / 2.0;
+    let bottom = y + height / 2.0;
+    let control = radius * (1.0 - KAPPA);
+
+    let mut builder = PathBuilder::new();
+    builder.move_to(left + radius, top);
+    builder.line_to(right - radius, top);
+    builder.cubic_to(
+        right - control,
+        top,
+        right,
+        top + control,
+        right,
+        top + radius,
+    );
+    builder.line_to(right, bottom - radius);
+    builder.cubic_to(
+        right,
+        bottom - control,
+        right - control,
+        bottom,
+        right - radius,
+        bottom,
+    );
+    builder.line_to(left + radius, bottom);
+    builder.cubic_to(
+        left + control,
+        bottom,
+        left,
+        bottom - control,
+        left,
+        bottom - radius,
+    );
+    builder.line_to(left, top + radius);
+    builder.cubic_to(left, top + control, left + control, top, left + radius, top);
+    builder.close();
+
+    builder.finish()
+}
+
+fn ellipse(item: &Value, time: f32) -> Option<Path> {
+    let [x, y] = vector(item.get("p"), time)?;
+    let [width, height] = vector(item.get("s"), time)?;
+
+    PathBuilder::from_oval(Rect::from_xywh(
+        x - width / 2.0,
+        y - height / 2.0,
+        width,
+        height,
+    )?)
+}
+
+fn bezier(property: Option<&Value>, time: f32) -> Option<Path> {
+    let shape = keyframed(property, time, |start, end, progress| {
+        let start_shape = start.get(0).unwrap_or(start);
+        let Some(end_shape) = end.map(|end_value| end_value.get(0).unwrap_or(end_value)) else {
+            return BezierShape::from_value(start_shape);
+        };
+
+        Some(
+            BezierShape::from_value(start_shape)?
+                .lerp(&BezierShape::from_value(end_shape)?, progress),
+        )
+    })?;
+
+    let mut builder = PathBuilder::new();
+    let (first, rest) = shape.vertices.split_first()?;
+    builder.move_to(first[0], first[1]);
+
+    let segment_count = if shape.closed {
+        shape.vertices.len()
+    } else {
+        rest.len()
+    };
+    for index in 0..segment_count {
+        let next_index = index.saturating_add(1).rem_euclid(shape.vertices.len());
+        let (Some(vertex), Some(out_tangent), Some(next_vertex), Some(in_tangent)) = (
+            shape.vertices.get(index),
+            shape.out_tangents.get(index),
+            shape.vertices.get(next_index),
+            shape.in_tangents.get(next_index),
+        ) else {
+            break;
+        };
+
+        builder.cubic_to(
+            vertex[0] + out_tangent[0],
+            vertex[1] + out_tangent[1],
+            next_vertex[0] + in_tangent[0],
+            next_vertex[1] + in_tangent[1],
+            next_vertex[0],
+            next_vertex[1],
+        );
+    }
+    if shape.closed {
+        builder.close();
+    }
+
+    builder.finish()
+}
+
+/// Vertices of a bezier path, tangents are relative to their vertices
+struct BezierShape {
+    vertices: Vec<[f32; 2]>,
+    in_tangents: Vec<[f32; 2]>,
+    out_tangents: Vec<[f32; 2]>,
+    closed: bool,
+}
+
+impl BezierShape {
+    fn from_value(shape: &Value) -> Option<Self> {
+        let points = |key| {
+            shape
+                .get(key)?
+                .as_array()?
+                .iter()
+                .map(|point| Some([number(point.get(0))?, number(point.get(1))?]))
+                .collect::<Option<Vec<_>>>()
+        };
+
+        Some(Self {
+            vertices: points("v")?,
+            in_tangents: points("i")?,
+            out_tangents: points("o")?,
+            closed: shape.get("c").and_then(Value::as_bool).unwrap_or(false),
+        })
+    }
+
+    fn lerp(self, end: &Self, progress: f32) -> Self {
+        let lerp_points = |start: Vec<[f32; 2]>, end_points: &[[f32; 2]]| {
+            if start.len() != end_points.len() {
+                return start;
+            }
+
+            start
+                .iter()
+                .zip(end_points)
+                .map(|(start_point, end_point)| {
+                    [
+                        lerp(start_point[0], end_point[0], progress),
+                        lerp(start_point[1], end_point[1], progress),
+                    ]
+                })
+                .collect()
+        };
+
+        Self {
+            vertices: lerp_points(self.vertices, &end.vertices),
+            in_tangents: lerp_points(self.in_tangents, &end.in_tangents),
+            out_tangents: lerp_points(self.out_tangents, &end.out_tangents),
+            closed: self.closed,
+        }
+    }
+}
+
+/// Return the paint of the fill or stroke
+fn paint(item: &Value, opacity: f32, time: f32) -> Option<Paint<'static>> {
+    let color = value(item.get("c"), time)?;
+    let mut channels = [*color.first()?, *color.get(1)?, *color.get(2)?];
+    // old versions use values up to 255
+    if channels.iter().any(|channel| *channel > 1.0) {
+        channels = channels.map(|channel| channel / 255.0);
+    }
+    let alpha = value(item.get("o"), time)
+        .and_then(|item_opacity| item_opacity.first().copied())
+        .unwrap_or(100.0)
+        / 100.0
+        * opacity;
+
+    let mut paint = Paint::default();
+    paint.set_color(Color::from_rgba(
+        channels[0].clamp(0.0, 1.0),
+        channels[1].clamp(0.0, 1.0),
+        channels[2].clamp(0.0, 1.0),
+        alpha.clamp(0.0, 1.0),
+    )?);
+
+    Some(paint)
+}
+
+/// Return the transform and opacity set by the `tr` item of the group
+fn group_transform(items: &[Value], time: f32) -> (Transform, f32) {
+    transform(
+        items
+            .iter()
+            .find(|item| item.get("ty").and_then(Value::as_str) == Some("tr")),
+        time,
+    )
+}
+
+/// Return the transform and opacity of the transform property
+fn transform(property: Option<&Value>, time: f32) -> (Transform, f32) {
+    let Some(property_inner) = property else {
+        return (Transform::identity(), 1.0);
+    };
+
+    let [anchor_x, anchor_y] = vector(property_inner.get("a"), time).unwrap_or([0.0, 0.0]);
+    let [position_x, position_y] = position(property_inner.get("p"), time).unwrap_or([0.0, 0.0]);
+    let [scale_x, scale_y] = vector(property_inner.get("s"), time).unwrap_or([100.0, 100.0]);
+    let rotation = value(
+        property_inner.get("r").or_else(|| property_inner.get("rz")),
+        time,
+    )
+    .and_then(|rotation_value| rotation_value.first().copied())
+    .unwrap_or(0.0);
+    let opacity = value(property_inner.get("o"), time)
+        .and_then(|opacity_value| opacity_value.first().copied())
+        .unwrap_or(100.0)
+        / 100.0;
+
+    (
+        Transform::from_translate(position_x, position_y)
+            .pre_rotate(rotation)
+            .pre_scale(scale_x / 100.0, scale_y / 100.0)
+            .pre_translate(-anchor_x, -anchor_y),
+        opacity,
+    )
+}
+
+/// Return the position, which can have its dimensions animated separately
+fn position(property: Option<&Value>, time: f32) -> Option<[f32; 2]> {
+    let property_inner = property?;
+    if property_inner.get("s").and_then(Value::as_bool) == Some(true) {
+        return Some([
+            *value(property_inner.get("x"), time)?.first()?,
+            *value(property_inner.get("y"), time)?.first()?,
+        ]);
+    }
+
+    vector(property, time)
+}
+
+/// Return the first two numbers of the property
+fn vector(property: Option<&Value>, time: f32) -> Option<[f32; 2]> {
+    let numbers = value(property, time)?;
+
+    Some([*numbers.first()?, *numbers.get(1)?])
+}
+
+/// Return the numbers of the property at the time
+fn value(property: Option<&Value>, time: f32) -> Option<Vec<f32>> {
+    keyframed(property, time, |start, end, progress| {
+        let start_numbers = numbers(start)?;
+        let Some(end_numbers) = end.and_then(numbers) else {
+            return Some(start_numbers);
+        };
+
+        Some(
+            start_numbers
+                .iter()
+                .zip(
+                    end_numbers
+                        .iter()
+                        .chain(start_numbers.iter().skip(end_numbers.len())),
+                )
+                .map(|(start_number, end_number)| lerp(*start_number, *end_number, progress))
+                .collect(),
+        )
+    })
+}
+
+/// Return the interpolated value of the property at the time
+///
+/// `interpolate` is called with the start value, the end value if there is
+/// one and the eased progress between them
+fn keyframed<T>(
+    property: Option<&Value>,
+    time: f32,
+    interpolate: impl Fn(&Value, Option<&Value>, f32) -> Option<T>,
+) -> Option<T> {
+    let keyframes_or_value = property?.get("k")?;
+    let Some(keyframes) = keyframes_or_value.as_array().filter(|keyframes| {
+        keyframes
+            .first()
+            .is_some_and(|keyframe| keyframe.get("t").is_some())
+    }) else {
+        return interpolate(keyframes_or_value, None, 0.0);
+    };
+
+    let (first, rest) = keyframes.split_first()?;
+    if number(first.get("t")).is_some_and(|start_time| time < start_time) {
+        return interpolate(first.get("s")?, None, 0.0);
+    }
+
+    let mut keyframe = first;
+    for next_keyframe in rest {
+        let next_time = number(next_keyframe.get("t"))?;
+        if time < next_time {
+            let start_time = number(keyframe.get("t"))?;
+            let start = keyframe.get("s")?;
+            let end = keyframe.get("e").or_else(|| next_keyframe.get("s"));
+
+            if keyframe.get("h").and_then(Value::as_u64) == Some(1) || next_time <= start_time {
+                return interpolate(start, None, 0.0);
+            }
+
+            let progress = ease(keyframe, (time - start_time) / (next_time - start_time));
+            return interpolate(start, end, progress);
+        }
+        keyframe = next_keyframe;
+    }
+
+    // the last keyframe may only have a time, the previous one has the end value then
+    let last_value = keyframe.get("s").or_else(|| {
+        keyframes
+            .iter()
+            .rev()
+            .skip(1)
+            .find_map(|previous_keyframe| {
+                previous_keyframe
+                    .get("e")
+                    .or_else(|| previous_keyframe.get("s"))
+            })
+    })?;
+
+    interpolate(last_value, None, 0.0)
+}
+
+/// Return the eased progress using the keyframe's bezier easing handles
+fn ease(keyframe: &Value, progress: f32) -> f32 {
+    let handle = |key, axis| {
+        let component = keyframe.get(key)?.get(axis)?;
+        number(component.get(0)).or_else(|| number(Some(component)))
+    };
+    let (Some(out_x), Some(out_y), Some(in_x), Some(in_y)) = (
+        handle("o", "x"),
+        handle("o", "y"),
+        handle("i", "x"),
+        handle("i", "y"),
+    ) else {
+        return progress;
+    };
+
+    let cubic = |first: f32, second: f32, parameter: f32| {
+        let inverse = 1.0 - parameter;
+        (parameter * parameter).mul_add(
+            parameter,
+            (3.0 * inverse * inverse * parameter)
+                .mul_add(first, 3.0 * inverse * parameter * parameter * second),
+        )
+    };
+
+    // find the curve parameter for the progress with bisection
+    let mut low = 0.0;
+    let mut high = 1.0;
+    for _ in 0..20_u8 {
+        let middle = (low + high) * 0.5;
+        if cubic(out_x, in_x, middle) < progress {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    cubic(out_y, in_y, (low + high) * 0.5)
+}
+
+/// Return the numbers in the value, which is a number or an array of numbers
+fn numbers(value: &Value) -> Option<Vec<f32>> {
+    if let Some(number_value) = number(Some(value)) {
+        return Some(vec![number_value]);
+    }
+
+    value
+        .as_array()?
+        .iter()
+        .map(|number_value| number(Some(number_value)))
+        .collect()
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn number(value: Option<&Value>) -> Option<f32> {
+    value?.as_f64().map(|number_value| number_value as f32)
+}
+
+fn lerp(start: f32, end: f32, progress: f32) -> f32 {
+    (end - start).mul_add(progress, start)
+}
@@ -80,7 +80,7 @@ impl MessageSource<'_> {
             let mut avatar_info = avatar::Info {
                 url: None,
                 user_id: message.author.id,
-                guild_id: self.guild_id,
+                guild_id: self.source_guild_id,
                 user_discriminator: message.author.discriminator,
                 user_avatar: message.author.avatar,
                 member_avatar: message.member.as_ref().and_then(|member| member.avatar),
@@ -101,7 +101,10 @@ impl MessageSource<'_> {
 
             embed.url = Some(format!(
                 "https://discord.com/channels/{}/{}/{}",
-                self.guild_id, message.channel_id, message.id
+                self.source_guild_id
+                    .map_or_else(|| "@me".to_owned(), |guild_id| guild_id.to_string()),
+                message.channel_id,
+                message.id
             ));
         }
 
@@ -0,0 +1,71 @@
+use twilight_model::channel::{
+    message::{
+        embed::{EmbedField, EmbedFooter, EmbedThumbnail},
+        Embed, MessageActivityType,
+    },
+    Message,
+};
+
+/// Return an embed describing the message's activity and application
+///
+/// The embed is labelled as a non-interactive copy since bots can't create
+/// activity invites
+pub(crate) fn embed(message: &Message) -> Embed {
+    let mut fields = vec![];
+
+    if let Some(activity) = &message.activity {
+        fields.push(EmbedField {
+            inline: true,
+            name: "Type".to_owned(),
+            value: match activity.kind {
+                MessageActivityType::Join => "Join",
+                MessageActivityType::Spectate => "Spectate",
+                MessageActivityType::Listen => "Listen",
+                MessageActivityType::JoinRequest => "Join request",
+                _ => "Unknown",
+            }
+            .to_owned(),
+        });
+
+        if let Some(party_id) = &activity.party_id {
+            fields.push(EmbedField {
+                inline: true,
+                name: "Party".to_owned(),
+                value: party_id.clone(),
+            });
+        }
+    }
+
+    Embed {
+        title: Some("Activity invite".to_owned()),
+        author: None,
+        color: None,
+        description: message
+            .application
+            .as_ref()
+            .map(|application| format!("**{}**", application.name)),
+        fields,
+        footer: Some(EmbedFooter {
+            icon_url: None,
+            proxy_icon_url: None,
+            text: "This is a non-interactive copy, it can't be joined".to_owned(),
+        }),
+        image: None,
+        kind: String::new(),
+        provider: None,
+        thumbnail: message.application.as_ref().and_then(|application| {
+            application.icon.map(|icon| EmbedThumbnail {
+                height: None,
+                proxy_url: None,
+                url: format!(
+                    "https://cdn.discordapp.com/app-icons/{}/{icon}.png",
+                    application.id
+                ),
+                width: None,
+            })
+        }),
+        timestamp: None,
+        url: None,
+        video: None,
+    }
+}
@@ -0,0 +1,155 @@
+use twilight_validate::message::MESSAGE_CONTENT_LENGTH_MAX;
+
+/// Length of the newline and backticks that close a code block
+const CODE_BLOCK_CLOSE_LEN: usize = 4;
+/// Maximum length of a code block's language to be kept in the next part
+const CODE_BLOCK_LANGUAGE_LEN_MAX: usize = 32;
+
+/// Split the content into parts that are at most
+/// [`MESSAGE_CONTENT_LENGTH_MAX`] characters
+///
+/// Splits at the last newline in the part, or the last space if there are
+/// none, if the part is in a code block, the code block is closed at the end of
+/// the part and reopened in the next part with the same language
+pub(crate) fn parts(content: &str) -> Vec<String> {
+    let mut parts = vec![];
+    let mut rest = content.to_owned();
+
+    while rest.chars().count() > MESSAGE_CONTENT_LENGTH_MAX {
+        // leave room to close a code block
+        let window_len = rest
+            .char_indices()
+            .nth(MESSAGE_CONTENT_LENGTH_MAX - CODE_BLOCK_CLOSE_LEN)
+            .map_or(rest.len(), |(i, _)| i);
+        let (window, _) = rest.split_at(window_len);
+
+        // don't split right after the line opening a code block
+        let split_min = if window.starts_with("```") {
+            window.find('\n').unwrap_or(0)
+        } else {
+            0
+        };
+
+        let (split_at, separator_len) = window
+            .rfind('\n')
+            .filter(|i| *i > split_min)
+            .or_else(|| window.rfind(' ').filter(|i| *i > split_min))
+            .map_or((window_len, 0), |i| (i, 1));
+
+        let (part_str, separator_and_remainder) = rest.split_at(split_at);
+        let (_, remainder_str) = separator_and_remainder.split_at(separator_len);
+
+        let mut part = part_str.to_owned();
+        let mut remainder = remainder_str.to_owned();
+        if let Some(language) = open_code_block_language(&part).map(ToOwned::to_owned) {
+            part.push_str("\n```");
+            remainder.insert_str(0, &format!("```{language}\n"));
+        }
+
+        parts.push(part);
+        rest = remainder;
+    }
+
+    parts.push(rest);
+
+    parts
+}
+
+/// Join the contents of two messages with a newline so that they render the
+/// same as the separate messages
+///
+/// If the first content leaves a code block open, it's closed, if it has a
+/// multi-line block quote, it's turned into single-line block quotes so that
+/// it doesn't quote the second content, if it ends with a list item, an empty
+/// line is added so that the second content doesn't continue the list
+pub(crate) fn join(first: &str, second: &str) -> String {
+    if first.is_empty() {
+        return second.to_owned();
+    }
+    if second.is_empty() {
+        return first.to_owned();
+    }
+
+    let mut joined = first.to_owned();
+    if open_code_block_language(&joined).is_some() {
+        joined.push_str("\n```");
+    }
+    joined = single_line_quotes(&joined);
+
+    if joined.lines().last().is_some_and(is_list_item) {
+        joined.push('\n');
+    }
+    joined.push('\n');
+    joined.push_str(second);
+
+    joined
+}
+
+/// Replace the multi-line block quote in the content with single-line block
+/// quotes on each line after it
+fn single_line_quotes(content: &str) -> String {
+    let mut lines = vec![];
+    let mut is_in_code_block = false;
+    let mut is_in_quote = false;
+
+    for line in content.split('\n') {
+        let is_code_block_line = is_in_code_block;
+        if line.matches("```").count() % 2 == 1 {
+            is_in_code_block = !is_in_code_block;
+        }
+
+        if is_in_quote {
+            lines.push(format!("> {line}"));
+            continue;
+        }
+
+        match line.strip_prefix(">>> ") {
+            Some(quoted) if !is_code_block_line => {
+                is_in_quote = true;
+                lines.push(format!("> {quoted}"));
+            }
+            _ => lines.push(line.to_owned()),
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Return whether the line is an item of an unordered or ordered list
+fn is_list_item(line: &str) -> bool {
+    let line_trimmed = line.trim_start();
+
+    line_trimmed.starts_with("- ")
+        || line_trimmed.starts_with("* ")
+        || line_trimmed.split_once(". ").is_some_and(|(number, _)| {
+            !number.is_empty() && number.chars().all(|c| c.is_ascii_digit())
+        })
+}
+
+/// Return the language of the code block that's open at the end of the
+/// content
+///
+/// Returns `None` if there is no open code block, or an empty string if the
+/// open code block has no language
+fn open_code_block_language(content: &str) -> Option<&str> {
+    let mut language = None;
+
+    for (i, segment) in content.split("```").enumerate().skip(1) {
+        language = (i % 2 == 1).then(|| {
+            let first_line = segment
+                .split_once('\n')
+                .map_or(segment, |(line, _)| line)
+                .trim();
+
+            if first_line.len() <= CODE_BLOCK_LANGUAGE_LEN_MAX
+                && !first_line.contains(char::is_whitespace)
+            {
+                first_line
+            } else {
+                ""
+            }
+        });
+    }
+
+    language
+}
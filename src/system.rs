@@ -0,0 +1,135 @@
+use twilight_model::{
+    channel::{message::MessageType, Message},
+    id::{marker::GuildMarker, Id},
+};
+
+use crate::error::Error;
+
+/// Return the text to show for a system message
+///
+/// Messages that aren't system messages, such as command responses, return
+/// their content as it is
+///
+/// # Errors
+///
+/// Returns [`Error::System`] if the message's type is unknown
+pub(crate) fn content(
+    message: &Message,
+    source_guild_id: Option<Id<GuildMarker>>,
+) -> Result<String, Error> {
+    let name = message
+        .member
+        .as_ref()
+        .and_then(|member| member.nick.as_ref())
+        .unwrap_or(&message.author.name);
+    let mention_name = message
+        .mentions
+        .first()
+        .map_or("someone", |mention| mention.name.as_str());
+
+    if let Some(data) = &message.role_subscription_data {
+        return Ok(format!(
+            "*{name} {} **{}** and has been a subscriber for {} months*",
+            if data.is_renewal { "renewed" } else { "joined" },
+            data.tier_name,
+            data.total_months_subscribed
+        ));
+    }
+
+    let content = match message.kind {
+        MessageType::Regular
+        | MessageType::Reply
+        | MessageType::ChatInputCommand
+        | MessageType::ContextMenuCommand
+        | MessageType::InteractionPremiumUpsell => return Ok(message.content.clone()),
+        MessageType::RecipientAdd => format!("{name} added {mention_name} to the group"),
+        MessageType::RecipientRemove
+            if message
+                .mentions
+                .first()
+                .map_or(message.author.id, |mention| mention.id)
+                == message.author.id =>
+        {
+            format!("{name} left the group")
+        }
+        MessageType::RecipientRemove => format!("{name} removed {mention_name} from the group"),
+        MessageType::Call => format!("{name} started a call"),
+        MessageType::ChannelNameChange => {
+            format!("{name} changed the channel name: **{}**", message.content)
+        }
+        MessageType::ChannelIconChange => format!("{name} changed the channel icon"),
+        MessageType::ChannelMessagePinned => {
+            format!("{name} pinned {}", reference_link(message, source_guild_id))
+        }
+        MessageType::UserJoin => format!("{name} joined the server"),
+        MessageType::GuildBoost => match message.content.parse::<u8>() {
+            Ok(count) if count > 1 => format!("{name} boosted the server {count} times"),
+            _ => format!("{name} boosted the server"),
+        },
+        MessageType::GuildBoostTier1 => {
+            format!("{name} boosted the server, it has achieved **Level 1**")
+        }
+        MessageType::GuildBoostTier2 => {
+            format!("{name} boosted the server, it has achieved **Level 2**")
+        }
+        MessageType::GuildBoostTier3 => {
+            format!("{name} boosted the server, it has achieved **Level 3**")
+        }
+        MessageType::ChannelFollowAdd => {
+            format!("{name} added **{}** to this channel", message.content)
+        }
+        MessageType::GuildDiscoveryDisqualified => {
+            "This server has been removed from Server Discovery".to_owned()
+        }
+        MessageType::GuildDiscoveryRequalified => {
+            "This server is eligible for Server Discovery again".to_owned()
+        }
+        MessageType::GuildDiscoveryGracePeriodInitialWarning => {
+            "This server has failed Server Discovery activity requirements for 1 week".to_owned()
+        }
+        MessageType::GuildDiscoveryGracePeriodFinalWarning => {
+            "This server has failed Server Discovery activity requirements for 3 weeks in a row"
+                .to_owned()
+        }
+        MessageType::ThreadCreated => {
+            format!("{name} started a thread: **{}**", message.content)
+        }
+        MessageType::ThreadStarterMessage => {
+            format!(
+                "This thread was started from {}",
+                reference_link(message, source_guild_id)
+            )
+        }
+        MessageType::GuildInviteReminder => "Wondering who to invite? Start by inviting anyone \
+                                             who can help you build the server!"
+            .to_owned(),
+        MessageType::AutoModerationAction => "AutoMod has blocked a message".to_owned(),
+        MessageType::StageStart => format!("{name} started **{}**", message.content),
+        MessageType::StageEnd => format!("{name} ended **{}**", message.content),
+        MessageType::StageSpeaker => format!("{name} is now a speaker"),
+        MessageType::StageTopic => {
+            format!("{name} changed the Stage topic: **{}**", message.content)
+        }
+        MessageType::GuildApplicationPremiumSubscription => {
+            format!("{name} upgraded an app to premium for this server")
+        }
+        _ => return Err(Error::System),
+    };
+
+    Ok(format!("*{content}*"))
+}
+
+fn reference_link(message: &Message, source_guild_id: Option<Id<GuildMarker>>) -> String {
+    let Some((channel_id, message_id)) = message
+        .reference
+        .as_ref()
+        .and_then(|reference| Some((reference.channel_id?, reference.message_id?)))
+    else {
+        return "a message".to_owned();
+    };
+
+    format!(
+        "[a message](https://discord.com/channels/{}/{channel_id}/{message_id})",
+        source_guild_id.map_or_else(|| "@me".to_owned(), |guild_id| guild_id.to_string())
+    )
+}
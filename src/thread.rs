@@ -1,12 +1,20 @@
 //! Handling the message being in a thread
 
+#[cfg(doc)]
+use twilight_model::guild::Permissions;
 use twilight_model::{
     channel::{Channel, ChannelType},
-    id::{marker::ChannelMarker, Id},
+    id::{
+        marker::{ChannelMarker, UserMarker},
+        Id,
+    },
 };
 
 use crate::{error::Error, MessageSource};
 
+/// Media channels aren't yet modelled in [`ChannelType`]
+const GUILD_MEDIA: ChannelType = ChannelType::Unknown(16);
+
 /// Info about the thread the message is in
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Info {
@@ -15,6 +23,10 @@ pub enum Info {
     ///
     /// Wrapped value is the thread's ID
     In(Id<ChannelMarker>),
+    /// Message is in a private thread
+    ///
+    /// Wrapped value is the thread
+    InPrivate(Box<Channel>),
     /// A non-post thread has been created from the message
     ///
     /// Wrapped value is the thread
@@ -23,8 +35,12 @@ pub enum Info {
     ///
     /// Wrapped value is the thread
     CreatedPost(Box<Channel>),
+    /// A post in a media channel has been created from the message
+    ///
+    /// Wrapped value is the thread
+    CreatedMediaPost(Box<Channel>),
     /// A thread has been created from the message, but it's not known whether
-    /// it's a forum channel post or not
+    /// it's a forum or media channel post or not
     ///
     /// Wrapped value is the thread
     CreatedUnknown(Box<Channel>),
@@ -34,13 +50,26 @@ pub enum Info {
     Unknown,
 }
 
+/// State of an archived or locked thread the message is created in
+///
+/// Used to restore the thread's state after the message is created
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchivedState {
+    /// Whether the thread was archived
+    pub archived: bool,
+    /// Whether the thread was locked
+    pub locked: bool,
+}
+
 impl Info {
     pub(crate) fn id(&self) -> Option<Id<ChannelMarker>> {
         match self {
             Self::In(id) => Some(*id),
-            Self::Created(thread) | Self::CreatedPost(thread) | Self::CreatedUnknown(thread) => {
-                Some(thread.id)
-            }
+            Self::InPrivate(thread)
+            | Self::Created(thread)
+            | Self::CreatedPost(thread)
+            | Self::CreatedMediaPost(thread)
+            | Self::CreatedUnknown(thread) => Some(thread.id),
             _ => None,
         }
     }
@@ -82,11 +111,13 @@ impl<'a> MessageSource<'a> {
         self.thread_info = if self.source_id == thread.id.cast() {
             let channel = self.http.channel(self.channel_id).await?.model().await?;
 
-            if channel.kind == ChannelType::GuildForum {
-                Info::CreatedPost(thread)
-            } else {
-                Info::Created(thread)
+            match channel.kind {
+                ChannelType::GuildForum => Info::CreatedPost(thread),
+                GUILD_MEDIA => Info::CreatedMediaPost(thread),
+                _ => Info::Created(thread),
             }
+        } else if thread.kind == ChannelType::PrivateThread {
+            Info::InPrivate(thread)
         } else {
             Info::In(thread.id)
         };
@@ -96,6 +127,256 @@ impl<'a> MessageSource<'a> {
         Ok(self)
     }
 
+    /// Handle the post being cloned to another forum or media channel
+    ///
+    /// This requests the channel set in [`MessageSource::channel_id`] to check
+    /// whether it's a forum or a media channel, so that the post is created with
+    /// the destination channel's requirements in mind
+    ///
+    /// Does nothing if the message isn't a post, so it's not necessary to call
+    /// this when the channel isn't changed
+    ///
+    /// # Warnings
+    ///
+    /// Must be called after [`MessageSource::handle_thread`] and after
+    /// [`MessageSource::channel_id`] is set to the destination channel
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MediaPostAttachment`] if the destination is a media
+    /// channel and the message has no attachments to upload
+    ///
+    /// Returns [`Error::Http`] if getting the channel fails
+    ///
+    /// Returns [`Error::DeserializeBody`] if deserializing the channel fails
+    pub async fn handle_post_destination(mut self) -> Result<MessageSource<'a>, Error> {
+        let (Info::CreatedPost(thread) | Info::CreatedMediaPost(thread)) = self.thread_info else {
+            return Ok(self);
+        };
+
+        let channel = self.http.channel(self.channel_id).await?.model().await?;
+
+        self.thread_info = if channel.kind == GUILD_MEDIA {
+            Info::CreatedMediaPost(thread)
+        } else {
+            Info::CreatedPost(thread)
+        };
+
+        self.check_media_post()?;
+
+        Ok(self)
+    }
+
+    /// Return [`Error::MediaPostAttachment`] if the message is a media channel
+    /// post without attachments to upload
+    pub(crate) const fn check_media_post(&self) -> Result<(), Error> {
+        if !matches!(self.thread_info, Info::CreatedMediaPost(_)) {
+            return Ok(());
+        }
+
+        #[cfg(feature = "upload")]
+        if !self.attachment_sticker_info.attachments_upload.is_empty()
+            || !self.attachment_sticker_info.attachments_deferred.is_empty()
+        {
+            return Ok(());
+        }
+
+        Err(Error::MediaPostAttachment)
+    }
+
+    /// Handle the thread the message is created in being archived or locked
+    ///
+    /// This unarchives and unlocks the thread, then archives and locks it again
+    /// in [`MessageSource::create`] after the message is created
+    ///
+    /// This requires getting the thread with another HTTP request, and updating
+    /// it twice if it's archived or locked
+    ///
+    /// Make sure the bot has these additional permissions if the thread is
+    /// locked:
+    /// - [`Permissions::MANAGE_THREADS`]
+    ///
+    /// # Warnings
+    ///
+    /// Must be called after [`MessageSource::handle_thread`] and before
+    /// [`MessageSource::create`]
+    ///
+    /// Later messages created in the same thread will have to call this too,
+    /// since the thread is archived again after each message
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ThreadLocked`] if the thread is locked and the bot
+    /// doesn't have [`Permissions::MANAGE_THREADS`]
+    ///
+    /// Returns [`Error::Http`] if getting or updating the thread fails
+    ///
+    /// Returns [`Error::DeserializeBody`] if deserializing the thread fails
+    pub async fn handle_thread_archived(mut self) -> Result<MessageSource<'a>, Error> {
+        let thread_id = match &self.thread_info {
+            Info::In(thread_id) => *thread_id,
+            Info::InPrivate(thread) => thread.id,
+            _ => return Ok(self),
+        };
+
+        let Some(metadata) = self
+            .http
+            .channel(thread_id)
+            .await?
+            .model()
+            .await?
+            .thread_metadata
+        else {
+            return Ok(self);
+        };
+
+        if !metadata.archived && !metadata.locked {
+            return Ok(self);
+        }
+
+        match self
+            .http
+            .update_thread(thread_id)
+            .archived(false)
+            .locked(false)
+            .await
+        {
+            Ok(_) => {}
+            Err(err)
+                if metadata.locked
+                    && matches!(
+                        err.kind(),
+                        twilight_http::error::ErrorType::Response { status, .. }
+                        if status.get() == 403
+                    ) =>
+            {
+                return Err(Error::ThreadLocked);
+            }
+            Err(err) => return Err(Error::Http(err)),
+        }
+
+        self.thread_archived = Some(ArchivedState {
+            archived: metadata.archived,
+            locked: metadata.locked,
+        });
+
+        Ok(self)
+    }
+
+    /// Restore the state of the thread set in
+    /// [`MessageSource::handle_thread_archived`]
+    pub(crate) async fn restore_thread_archived(&mut self) -> Result<(), Error> {
+        let (Some(state), Some(thread_id)) = (self.thread_archived.take(), self.thread_info.id())
+        else {
+            return Ok(());
+        };
+
+        self.http
+            .update_thread(thread_id)
+            .archived(state.archived)
+            .locked(state.locked)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Handle the message being cloned from a private thread to another channel
+    ///
+    /// This creates a private thread with the same name, auto archive duration
+    /// and invitable setting in [`MessageSource::channel_id`] and creates the
+    /// message in it
+    ///
+    /// If `add_members` is `true`, adds the members of the original thread to
+    /// the new thread, this requires another HTTP request for every 100
+    /// members and another HTTP request for each member
+    ///
+    /// Does nothing if the message isn't in a private thread
+    ///
+    /// Make sure the bot has these additional permissions:
+    /// - [`Permissions::CREATE_PRIVATE_THREADS`]
+    /// - [`Permissions::SEND_MESSAGES_IN_THREADS`]
+    ///
+    /// # Warnings
+    ///
+    /// Must be called after [`MessageSource::handle_thread`] and after
+    /// [`MessageSource::channel_id`] is set to the destination channel
+    ///
+    /// Getting the thread's members requires the `GUILD_MEMBERS` intent
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ChannelValidation`] if the thread is invalid, shouldn't
+    /// happen unless the it was mutated
+    ///
+    /// Returns [`Error::Http`] if creating the thread, getting the thread's
+    /// members or adding them fails
+    ///
+    /// Returns [`Error::DeserializeBody`] if deserializing the thread or the
+    /// thread's members fails
+    #[allow(clippy::missing_panics_doc)]
+    pub async fn handle_private_thread(
+        mut self,
+        add_members: bool,
+    ) -> Result<MessageSource<'a>, Error> {
+        let Info::InPrivate(thread) = &self.thread_info else {
+            return Ok(self);
+        };
+
+        let mut create_thread = self.http.create_thread(
+            self.channel_id,
+            thread.name.as_ref().unwrap(),
+            ChannelType::PrivateThread,
+        )?;
+        if let Some(metadata) = &thread.thread_metadata {
+            create_thread = create_thread.auto_archive_duration(metadata.auto_archive_duration);
+            if let Some(invitable) = metadata.invitable {
+                create_thread = create_thread.invitable(invitable);
+            }
+        }
+        let thread_new = create_thread.await?.model().await?;
+
+        if add_members {
+            for user_id in self.thread_member_ids(thread.id).await? {
+                if Some(user_id) == thread_new.owner_id {
+                    continue;
+                }
+                self.http.add_thread_member(thread_new.id, user_id).await?;
+            }
+        }
+
+        self.thread_info = Info::InPrivate(Box::new(thread_new));
+
+        Ok(self)
+    }
+
+    async fn thread_member_ids(
+        &self,
+        thread_id: Id<ChannelMarker>,
+    ) -> Result<Vec<Id<UserMarker>>, Error> {
+        let mut user_ids = vec![];
+
+        loop {
+            // Discord ignores `after` and `limit` unless `with_member` is set
+            let mut get_members = self
+                .http
+                .thread_members(thread_id)
+                .with_member(true)
+                .limit(100)?;
+            if let Some(after) = user_ids.last() {
+                get_members = get_members.after(*after);
+            }
+
+            let member_batch = get_members.await?.models().await?;
+            let is_complete = member_batch.len() < 100;
+
+            user_ids.extend(member_batch.into_iter().filter_map(|member| member.user_id));
+
+            if is_complete {
+                return Ok(user_ids);
+            }
+        }
+    }
+
     /// Handle a thread being created from the message
     ///
     /// # Errors
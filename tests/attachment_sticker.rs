@@ -1,5 +1,21 @@
+#[cfg(feature = "upload")]
+use std::{sync::Arc, time::Duration};
+
 use common::Context;
 use sparkle_impostor::error::Error;
+#[cfg(feature = "upload")]
+use sparkle_impostor::{
+    attachment_sticker::{
+        AttachmentFetcher, DownloadOptions, FetchFuture, LottieSticker, MemoryUsage,
+        ReqwestFetcher, UploadPriority,
+    },
+    constructor::Options,
+    MessageSource,
+};
+#[cfg(feature = "upload")]
+use twilight_model::channel::message::MessageFlags;
+#[cfg(feature = "lottie")]
+use twilight_model::channel::message::sticker::MessageSticker;
 use twilight_model::{channel::message::sticker::StickerFormatType, http::attachment::Attachment};
 use twilight_validate::message::MESSAGE_CONTENT_LENGTH_MAX;
 
@@ -76,6 +92,72 @@ async fn sticker_link() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn link_spoiler_alt_text() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .content(
+            "attachment spoiler link *(should be cloned with a spoiler link and alt text at the \
+             bottom)*",
+        )?
+        .attachments(&[Attachment {
+            description: Some("alt text".to_owned()),
+            file: vec![1],
+            filename: "SPOILER_link.txt".to_owned(),
+            id: 0,
+        }])?
+        .await?
+        .model()
+        .await?;
+
+    let message_source = ctx.message_source(&mut message)?.handle_attachment_link()?;
+    assert!(message_source.content.ends_with(" (alt text)||"));
+    assert!(message_source.content.contains("\n\n||https://"));
+
+    message_source.create().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn embed() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .content("attachment embed *(should be cloned with an image embed and a list embed)*")?
+        .attachments(&[
+            Attachment {
+                description: None,
+                file: vec![1],
+                filename: "attachment_embed.png".to_owned(),
+                id: 0,
+            },
+            Attachment {
+                description: None,
+                file: vec![1],
+                filename: "attachment_embed.txt".to_owned(),
+                id: 1,
+            },
+        ])?
+        .await?
+        .model()
+        .await?;
+
+    message
+        .content
+        .push_str(&"a".repeat(MESSAGE_CONTENT_LENGTH_MAX - message.content.chars().count()));
+
+    ctx.message_source(&mut message)?
+        .handle_attachment_embed()?
+        .create()
+        .await?;
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn link_content_too_long() -> Result<(), anyhow::Error> {
     let ctx = Context::new().await;
@@ -137,3 +219,416 @@ async fn upload() -> Result<(), anyhow::Error> {
 
     Ok(())
 }
+
+#[cfg(feature = "upload")]
+#[tokio::test]
+async fn upload_embed_url() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .content("attachment upload embed url *(should be cloned with the image in an embed)*")?
+        .attachments(&[Attachment {
+            description: None,
+            file: vec![1],
+            filename: "upload_embed_url.png".to_owned(),
+            id: 0,
+        }])?
+        .await?
+        .model()
+        .await?;
+
+    // the embed links to the attachment like in bot messages
+    let message_source = ctx
+        .message_source(&mut message)?
+        .handle_attachment_embed()?
+        .handle_attachment_upload()
+        .await?;
+    assert_eq!(
+        message_source
+            .embeds
+            .first()
+            .unwrap()
+            .image
+            .as_ref()
+            .unwrap()
+            .url,
+        "attachment://upload_embed_url.png"
+    );
+
+    message_source.create().await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "upload")]
+#[tokio::test]
+async fn upload_concurrent() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let attachments = (0..4)
+        .map(|id| Attachment {
+            description: None,
+            file: vec![1],
+            filename: format!("upload_concurrent_{id}.txt"),
+            id,
+        })
+        .collect::<Vec<_>>();
+    let mut message = ctx
+        .create_message()
+        .content("attachment upload concurrent *(should be cloned with the attachments in order)*")?
+        .attachments(&attachments)?
+        .await?
+        .model()
+        .await?;
+
+    let mut message_source = ctx.message_source(&mut message)?;
+    message_source.download_options = DownloadOptions {
+        fetcher: Some(Arc::new(ReqwestFetcher {
+            client: reqwest::Client::new(),
+            timeout: Some(Duration::from_secs(10)),
+        })),
+        concurrency: 2,
+        deferred: false,
+    };
+
+    message_source
+        .handle_attachment_upload()
+        .await?
+        .create()
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "upload")]
+#[tokio::test]
+async fn upload_deferred() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .content("attachment upload deferred *(should be cloned with the attachment)*")?
+        .attachments(&[Attachment {
+            description: None,
+            file: vec![1],
+            filename: "upload_deferred.txt".to_owned(),
+            id: 0,
+        }])?
+        .await?
+        .model()
+        .await?;
+
+    let mut message_source = ctx.message_source(&mut message)?;
+    message_source.download_options.deferred = true;
+    let message_source = message_source.handle_attachment_upload().await?;
+    assert_eq!(
+        message_source.attachment_memory_usage(),
+        MemoryUsage {
+            buffered: 0,
+            deferred: 1
+        }
+    );
+
+    let message_source = message_source.create().await?;
+    assert_eq!(
+        message_source.attachment_memory_usage(),
+        MemoryUsage::default()
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "upload")]
+#[derive(Debug)]
+struct StubFetcher;
+
+#[cfg(feature = "upload")]
+impl AttachmentFetcher for StubFetcher {
+    fn fetch_url<'a>(&'a self, _url: &'a str) -> FetchFuture<'a> {
+        Box::pin(async { Ok(b"stub".to_vec()) })
+    }
+
+    fn fetch<'a>(&'a self, attachment: &'a twilight_model::channel::Attachment) -> FetchFuture<'a> {
+        Box::pin(async move {
+            if attachment.filename.starts_with("fail") {
+                return Err(Error::Fetch("stub fetcher failed".into()));
+            }
+
+            Ok(b"stub".to_vec())
+        })
+    }
+}
+
+#[cfg(feature = "upload")]
+#[tokio::test]
+async fn upload_fetcher() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .content("attachment upload fetcher *(should be cloned with an attachment saying stub)*")?
+        .attachments(&[Attachment {
+            description: None,
+            file: vec![1],
+            filename: "upload_fetcher.txt".to_owned(),
+            id: 0,
+        }])?
+        .await?
+        .model()
+        .await?;
+
+    let mut message_source = ctx.message_source(&mut message)?;
+    message_source.download_options.fetcher = Some(Arc::new(StubFetcher));
+    let message_source = message_source.handle_attachment_upload().await?;
+    assert_eq!(
+        message_source
+            .attachment_sticker_info
+            .attachments_upload
+            .first()
+            .unwrap()
+            .file,
+        b"stub"
+    );
+
+    message_source.create().await?;
+
+    message.attachments.first_mut().unwrap().filename = "fail.txt".to_owned();
+    let mut message_source = ctx.message_source(&mut message)?;
+    message_source.download_options.fetcher = Some(Arc::new(StubFetcher));
+    assert!(matches!(
+        message_source.handle_attachment_upload().await,
+        Err(Error::Fetch(_))
+    ));
+
+    Ok(())
+}
+
+#[cfg(feature = "upload")]
+#[tokio::test]
+async fn upload_size_limit() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .content("attachment upload size limit *(should not be cloned)*")?
+        .attachments(&[Attachment {
+            description: None,
+            file: vec![1],
+            filename: "upload_size_limit.txt".to_owned(),
+            id: 0,
+        }])?
+        .await?
+        .model()
+        .await?;
+
+    let mut message_source = ctx.message_source(&mut message)?;
+    message_source.attachment_sticker_info.upload_size_limit = Some(0);
+
+    assert!(matches!(
+        message_source.handle_attachment_upload().await,
+        Err(Error::AttachmentTooLarge)
+    ));
+
+    Ok(())
+}
+
+#[cfg(feature = "upload")]
+#[tokio::test]
+async fn upload_or_link() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .content(
+            "attachment upload or link *(should be cloned with the first attachment uploaded and \
+             a link to the second)*",
+        )?
+        .attachments(&[
+            Attachment {
+                description: None,
+                file: vec![1],
+                filename: "upload_or_link.txt".to_owned(),
+                id: 0,
+            },
+            Attachment {
+                description: None,
+                file: vec![1],
+                filename: "upload_or_link.png".to_owned(),
+                id: 1,
+            },
+        ])?
+        .await?
+        .model()
+        .await?;
+    // pretend the second attachment is too large to upload
+    message.attachments.last_mut().unwrap().size = 30 * 1024 * 1024;
+
+    let message_source = ctx
+        .message_source(&mut message)?
+        .handle_attachment_upload_or_link(UploadPriority::ImagesFirst)
+        .await?;
+    assert_eq!(
+        message_source
+            .attachment_sticker_info
+            .attachments_upload
+            .len(),
+        1
+    );
+    assert_eq!(
+        message_source
+            .attachment_sticker_info
+            .attachments_link
+            .len(),
+        1
+    );
+
+    message_source.create().await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "upload")]
+#[tokio::test]
+async fn sticker_upload() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let Some(sticker) = ctx
+        .http
+        .guild_stickers(ctx.guild_id)
+        .await?
+        .models()
+        .await?
+        .into_iter()
+        .find(|sticker| {
+            !matches!(
+                sticker.format_type,
+                StickerFormatType::Lottie | StickerFormatType::Unknown(_)
+            )
+        })
+    else {
+        ctx.create_message()
+            .content("can't test sticker uploads, guild doesn't have non-lottie sticker")?
+            .await?;
+        return Ok(());
+    };
+
+    let mut message = ctx
+        .create_message()
+        .content("sticker upload *(should be cloned with the sticker as an image)*")?
+        .sticker_ids(&[sticker.id])?
+        .await?
+        .model()
+        .await?;
+
+    ctx.message_source(&mut message)?
+        .handle_sticker_upload(LottieSticker::Error)
+        .await?
+        .create()
+        .await?;
+
+    message.sticker_items.first_mut().unwrap().format_type = StickerFormatType::Lottie;
+    message.content =
+        "sticker upload lottie *(should be cloned with a placeholder image and the sticker's \
+         name)*"
+            .to_owned();
+
+    assert!(matches!(
+        ctx.message_source(&mut message)?
+            .handle_sticker_upload(LottieSticker::Error)
+            .await,
+        Err(Error::StickerLinkInvalid)
+    ));
+
+    let message_source = ctx
+        .message_source(&mut message)?
+        .handle_sticker_upload(LottieSticker::Placeholder)
+        .await?;
+    assert!(message_source.content.contains("\n\n*Sticker: "));
+
+    message_source.create().await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "lottie")]
+#[tokio::test]
+async fn sticker_render() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let sticker = ctx
+        .http
+        .nitro_sticker_packs()
+        .await?
+        .model()
+        .await?
+        .sticker_packs
+        .into_iter()
+        .flat_map(|pack| pack.stickers)
+        .find(|sticker| sticker.format_type == StickerFormatType::Lottie)
+        .unwrap();
+
+    let mut message = ctx
+        .create_message()
+        .content("sticker render *(should be cloned with the sticker as an animated image)*")?
+        .await?
+        .model()
+        .await?;
+    message.sticker_items = vec![MessageSticker {
+        format_type: sticker.format_type,
+        id: sticker.id,
+        name: sticker.name,
+    }];
+
+    ctx.message_source(&mut message)?
+        .handle_sticker_upload(LottieSticker::Render)
+        .await?
+        .create()
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "upload")]
+#[tokio::test]
+async fn voice() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .attachments(&[Attachment {
+            description: None,
+            file: vec![1],
+            filename: "voice-message.ogg".to_owned(),
+            id: 0,
+        }])?
+        .await?
+        .model()
+        .await?;
+    message.guild_id = Some(ctx.guild_id);
+    message.flags = Some(MessageFlags::IS_VOICE_MESSAGE);
+    message.attachments.first_mut().unwrap().duration_secs = Some(65.5);
+
+    assert!(matches!(
+        MessageSource::from_message(&message, &ctx.http),
+        Err(Error::Voice)
+    ));
+
+    let message_source = MessageSource::from_message_with(
+        &message,
+        &ctx.http,
+        Options {
+            upload_voice: true,
+            ..Options::default()
+        },
+    )?;
+    assert_eq!(message_source.content, "*Voice message (1:05)*");
+
+    message_source
+        .handle_attachment_upload()
+        .await?
+        .create()
+        .await?;
+
+    Ok(())
+}
@@ -17,6 +17,7 @@ pub struct Context {
     pub guild_id: Id<GuildMarker>,
     pub channel_id: Id<ChannelMarker>,
     pub forum_channel_id: Id<ChannelMarker>,
+    pub media_channel_id: Id<ChannelMarker>,
     pub not_last_source_thread_id: Id<ChannelMarker>,
     pub guild_emoji_id: Id<EmojiMarker>,
     pub member: Member,
@@ -34,6 +35,7 @@ impl Context {
         let http = Client::new(env::var("BOT_TOKEN")?);
         let channel_id = env::var("CHANNEL_ID")?.parse()?;
         let forum_channel_id = env::var("FORUM_CHANNEL_ID")?.parse()?;
+        let media_channel_id = env::var("MEDIA_CHANNEL_ID")?.parse()?;
         let not_last_source_thread_id = if let Ok(var) = env::var("NOT_LAST_SOURCE_THREAD_ID") {
             var.parse()?
         } else {
@@ -75,6 +77,7 @@ impl Context {
             guild_id,
             channel_id,
             forum_channel_id,
+            media_channel_id,
             not_last_source_thread_id,
             guild_emoji_id,
             member,
@@ -185,14 +188,17 @@ fn _source_construct() {
         tts: false,
         flags: None,
         channel_id: Id::new(1),
+        source_guild_id: None,
         guild_id: Id::new(1),
         guild_emoji_ids: None,
+        #[cfg(feature = "upload")]
+        guild_premium_tier: None,
         username: String::new(),
         reference_info: sparkle_impostor::reference::Info::None,
         avatar_info: sparkle_impostor::avatar::Info {
             url: None,
             user_id: Id::new(1),
-            guild_id: Id::new(1),
+            guild_id: None,
             user_discriminator: 1,
             user_avatar: None,
             member_avatar: None,
@@ -202,22 +208,45 @@ fn _source_construct() {
         attachment_sticker_info: sparkle_impostor::attachment_sticker::Info {
             stickers: &[],
             attachments: &[],
+            refreshed_urls: None,
             #[cfg(feature = "upload")]
             attachments_upload: vec![],
+            #[cfg(feature = "upload")]
+            attachments_deferred: vec![],
+            #[cfg(feature = "upload")]
+            attachments_link: vec![],
+            #[cfg(feature = "upload")]
+            upload_size_limit: None,
         },
+        #[cfg(feature = "upload")]
+        download_options: sparkle_impostor::attachment_sticker::DownloadOptions::default(),
         component_info: sparkle_impostor::component::Info {
             url_components: vec![],
             has_invalid_components: false,
         },
+        interaction_info: sparkle_impostor::interaction::Info {
+            interaction: None,
+            is_chat_input: false,
+        },
         thread_info: sparkle_impostor::thread::Info::Unknown,
+        thread_archived: None,
+        earlier_messages: sparkle_impostor::earlier_messages::Info {
+            messages: vec![],
+            source_message: None,
+            is_earlier_message_sources_created: false,
+        },
         later_messages: sparkle_impostor::later_messages::Info {
             messages: vec![],
             is_complete: false,
             is_source_created: false,
+            created_message_count: 0,
+            until: sparkle_impostor::later_messages::Until::End,
+            last_message_id: None,
             is_later_message_sources_created: false,
         },
         webhook: None,
         response: None,
+        options: sparkle_impostor::constructor::Options::default(),
         http: &Client::new(String::new()),
     });
 }
@@ -0,0 +1,36 @@
+use common::Context;
+use sparkle_impostor::{constructor::Options, MessageSource};
+
+mod common;
+
+#[tokio::test]
+async fn dm() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let channel = ctx
+        .http
+        .create_private_channel(ctx.owner.user.id)
+        .await?
+        .model()
+        .await?;
+
+    let message = ctx
+        .http
+        .create_message(channel.id)
+        .content("dm message *(should be cloned to the channel with the global avatar)*")?
+        .await?
+        .model()
+        .await?;
+
+    MessageSource::from_dm_message(
+        &message,
+        &ctx.http,
+        ctx.channel_id,
+        ctx.guild_id,
+        Options::default(),
+    )?
+    .create()
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use common::Context;
+use sparkle_impostor::earlier_messages::Since;
+
+mod common;
+
+#[tokio::test]
+async fn earlier() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut earlier_messages = vec![];
+    for i in 1_u8..=3 {
+        earlier_messages.push(
+            ctx.create_message()
+                .content(&format!("earlier messages {i}"))?
+                .await?
+                .model()
+                .await?,
+        );
+    }
+    let second_message = earlier_messages.get(1).unwrap();
+
+    let mut message = ctx
+        .create_message()
+        .content(
+            "earlier messages *(should be cloned with the earlier messages 2 and 3 before it, in \
+             order)*",
+        )?
+        .await?
+        .model()
+        .await?;
+
+    for (since, count) in [(Since::Count(1), 1), (Since::Message(second_message.id), 2)] {
+        assert_eq!(
+            ctx.message_source(&mut message)?
+                .earlier_messages(since)
+                .await?
+                .len(),
+            count
+        );
+    }
+
+    let mut message_source = ctx.message_source(&mut message)?;
+    let earlier_message_sources = message_source.earlier_messages(Since::Count(2)).await?;
+
+    assert!(earlier_message_sources.iter().all(Result::is_ok));
+    for earlier_message in earlier_message_sources {
+        earlier_message?.create().await?;
+    }
+
+    message_source.create().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn earlier_and_later() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    ctx.create_message()
+        .content("earlier and later messages 1")?
+        .await?;
+
+    let mut message = ctx
+        .create_message()
+        .content(
+            "earlier and later messages 2 *(should be cloned with the messages 1 and 3 around it, \
+             in order)*",
+        )?
+        .await?
+        .model()
+        .await?;
+
+    ctx.create_message()
+        .content("earlier and later messages 3")?
+        .await?;
+
+    let mut message_source = ctx.message_source(&mut message)?;
+    let earlier_message_sources = message_source.earlier_messages(Since::Count(1)).await?;
+
+    assert!(earlier_message_sources.iter().all(Result::is_ok));
+    for earlier_message in earlier_message_sources {
+        earlier_message?.create().await?;
+    }
+
+    let mut message_source = message_source.create().await?;
+    let later_message_sources = message_source.later_messages().await?;
+
+    assert_eq!(later_message_sources.len(), 1);
+    for later_message in later_message_sources {
+        later_message?.create().await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn ordered() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    ctx.create_message().content("ordered messages 1")?.await?;
+
+    let mut message = ctx
+        .create_message()
+        .content(
+            "ordered messages 2 *(should be cloned with the messages 1 and 3 around it, in order)*",
+        )?
+        .await?
+        .model()
+        .await?;
+
+    ctx.create_message().content("ordered messages 3")?.await?;
+
+    let mut message_source = ctx.message_source(&mut message)?;
+    let username = message_source.username.clone();
+    let message_sources = message_source.ordered_messages(Since::Count(1)).await?;
+
+    assert_eq!(message_sources.len(), 3);
+    assert!(message_sources.iter().all(Result::is_ok));
+    assert_eq!(
+        message_sources
+            .get(1)
+            .and_then(|source| source.as_ref().ok())
+            .map(|source| source.username.as_str()),
+        Some(username.as_str())
+    );
+    for ordered_message in message_sources {
+        ordered_message?.create().await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use common::Context;
+use sparkle_impostor::interaction::Placement;
+use twilight_model::{
+    application::interaction::InteractionType,
+    channel::message::{MessageInteraction, MessageType},
+    id::Id,
+};
+
+mod common;
+
+#[tokio::test]
+async fn embed() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .content("interaction embed *(should be cloned with a header embed)*")?
+        .await?
+        .model()
+        .await?;
+    set_interaction(&ctx, &mut message);
+
+    ctx.message_source(&mut message)?
+        .handle_interaction(Placement::Embed)?
+        .create()
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn content_prefix() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .content("interaction content prefix *(should be cloned with a header line)*")?
+        .await?
+        .model()
+        .await?;
+    set_interaction(&ctx, &mut message);
+
+    ctx.message_source(&mut message)?
+        .handle_interaction(Placement::ContentPrefix)?
+        .create()
+        .await?;
+
+    Ok(())
+}
+
+fn set_interaction(ctx: &Context, message: &mut twilight_model::channel::Message) {
+    message.kind = MessageType::ChatInputCommand;
+    message.interaction = Some(MessageInteraction {
+        id: Id::new(1),
+        kind: InteractionType::ApplicationCommand,
+        member: None,
+        name: "sparkle".to_owned(),
+        user: ctx.owner.user.clone(),
+    });
+}
@@ -1,6 +1,11 @@
 use common::Context;
-use sparkle_impostor::error::Error;
-use twilight_model::id::Id;
+use sparkle_impostor::{
+    error::Error,
+    later_messages::{BatchRules, Until},
+};
+use twilight_model::{
+    channel::message::MessageType, http::attachment::Attachment, id::Id, util::Timestamp,
+};
 use twilight_validate::message::MESSAGE_CONTENT_LENGTH_MAX;
 
 use crate::common::create_later_messages;
@@ -50,6 +55,65 @@ async fn check_ok() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn until() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .content("later messages until *(should be cloned with the messages 1 and 2 below)*")?
+        .await?
+        .model()
+        .await?;
+
+    let mut later_messages = vec![];
+    for i in 1_u8..=3 {
+        later_messages.push(
+            ctx.create_message()
+                .content(&i.to_string())?
+                .await?
+                .model()
+                .await?,
+        );
+    }
+    let second_message = later_messages.get(1).unwrap();
+
+    for (until, count) in [
+        (Until::MessageInclusive(second_message.id), 2),
+        (Until::MessageExclusive(second_message.id), 1),
+        (Until::Timestamp(second_message.timestamp), 2),
+        (Until::Count(2), 2),
+    ] {
+        assert_eq!(
+            ctx.message_source(&mut message)?
+                .later_messages_until(until)
+                .later_messages()
+                .await?
+                .len(),
+            count
+        );
+    }
+
+    ctx.message_source(&mut message)?
+        .later_messages_until(Until::Count(1))
+        .check_is_in_last(2)
+        .await?;
+
+    let mut message_source = ctx
+        .message_source(&mut message)?
+        .later_messages_until(Until::MessageInclusive(second_message.id))
+        .create()
+        .await?;
+    let later_message_sources = message_source.later_messages().await?;
+
+    assert!(later_message_sources.iter().all(Result::is_ok));
+    for later_message in later_message_sources {
+        later_message?.create().await?;
+    }
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn create_later_thread() -> Result<(), anyhow::Error> {
     let ctx = Context::new().await;
@@ -140,6 +204,129 @@ async fn batched() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn batched_rules() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut messages = vec![];
+    for i in 1_u8..=7 {
+        let mut message = ctx
+            .create_message()
+            .content(&format!(
+                "batched messages with rules {i} *(should be cloned with 2 and 3, 6 and 7 \
+                 combined into one message)*"
+            ))?
+            .await?
+            .model()
+            .await?;
+
+        message.guild_id = Some(ctx.guild_id);
+
+        messages.push(message);
+    }
+    let later_timestamp = messages.get(4).unwrap().timestamp.as_micros() + 60 * 60 * 1_000_000;
+    messages.get_mut(4).unwrap().timestamp = Timestamp::from_micros(later_timestamp)?;
+    messages.get_mut(5).unwrap().kind = MessageType::Reply;
+
+    let mut first_message = messages.remove(0);
+    let mut message_source = ctx.message_source(&mut first_message)?.create().await?;
+
+    message_source.later_messages.messages = messages;
+    message_source.later_messages.is_complete = true;
+    message_source.later_messages.is_source_created = true;
+
+    let later_messages = message_source
+        .later_messages_batched_with(BatchRules {
+            max_count: Some(2),
+            ..BatchRules::default()
+        })
+        .await?;
+
+    assert_eq!(later_messages.len(), 4);
+    for later_message in later_messages {
+        later_message?.create().await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn batched_markdown() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .content(
+            "batched messages markdown *(should be cloned with the messages below combined, \
+             rendering the same as they do separately)*",
+        )?
+        .await?
+        .model()
+        .await?;
+
+    for content in [
+        "```rust\nlet code_block = \"left open\";",
+        ">>> multi-line block quote\nstill quoted",
+        "- list item",
+        "shouldn't be in a code block, block quote or list",
+    ] {
+        ctx.create_message().content(content)?.await?;
+    }
+
+    let mut message_source = ctx.message_source(&mut message)?.create().await?;
+
+    let later_messages = message_source.later_messages_batched().await?;
+
+    assert_eq!(later_messages.len(), 1);
+    for later_message in later_messages {
+        later_message?.create().await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn batched_attachments() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .content(
+            "batched messages attachments *(should be cloned with the first two messages \
+             combined, the last one separately)*",
+        )?
+        .await?
+        .model()
+        .await?;
+
+    for attachment_count in [6_usize, 4, 1] {
+        ctx.create_message()
+            .content(&format!("{attachment_count} attachments"))?
+            .attachments(
+                &(0..attachment_count)
+                    .map(|i| Attachment {
+                        description: None,
+                        file: vec![1],
+                        filename: format!("batched_{i}.txt"),
+                        id: i.try_into().unwrap(),
+                    })
+                    .collect::<Vec<_>>(),
+            )?
+            .await?;
+    }
+
+    let mut message_source = ctx.message_source(&mut message)?.create().await?;
+
+    let later_messages = message_source.later_messages_batched().await?;
+
+    assert_eq!(later_messages.len(), 2);
+    for later_message in later_messages {
+        later_message?.handle_attachment_link()?.create().await?;
+    }
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn batched_content_too_long() -> Result<(), anyhow::Error> {
     let ctx = Context::new().await;
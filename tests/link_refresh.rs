@@ -0,0 +1,84 @@
+use std::time::{Duration, SystemTime};
+
+use common::Context;
+use sparkle_impostor::link_refresh;
+use twilight_model::http::attachment::Attachment;
+
+mod common;
+
+#[tokio::test]
+async fn link() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .content("attachment refreshed link *(should be cloned with a link at the bottom)*")?
+        .attachments(&[Attachment {
+            description: None,
+            file: vec![1],
+            filename: "refreshed_link.txt".to_owned(),
+            id: 0,
+        }])?
+        .await?
+        .model()
+        .await?;
+
+    let message_source = ctx
+        .message_source(&mut message)?
+        .refresh_attachment_urls()
+        .await?;
+    let refreshed_url = message_source
+        .attachment_sticker_info
+        .refreshed_urls
+        .as_ref()
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+    assert!(link_refresh::url_expires_at(&refreshed_url)
+        .is_some_and(|expires_at| expires_at > SystemTime::now()));
+
+    message_source.handle_attachment_link()?.create().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn relink() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .content("attachment relink *(should be cloned with a link at the bottom, then edited)*")?
+        .attachments(&[Attachment {
+            description: None,
+            file: vec![1],
+            filename: "relink.txt".to_owned(),
+            id: 0,
+        }])?
+        .await?
+        .model()
+        .await?;
+
+    let mut message_source = ctx
+        .message_source(&mut message)?
+        .handle_attachment_link()?
+        .create()
+        .await?;
+    let message_cloned = message_source.response.as_mut().unwrap().model().await?;
+
+    assert!(
+        !link_refresh::relink_expiring_attachments(&ctx.http, message_cloned, Duration::ZERO)
+            .await?
+    );
+    assert!(
+        link_refresh::relink_expiring_attachments(
+            &ctx.http,
+            message_cloned,
+            Duration::from_secs(365 * 24 * 60 * 60)
+        )
+        .await?
+    );
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use common::Context;
+use sparkle_impostor::{constructor::Options, error::Error, MessageSource};
+use twilight_model::{
+    channel::message::{MessageActivity, MessageActivityType, MessageApplication},
+    id::Id,
+};
+
+mod common;
+
+#[tokio::test]
+async fn render() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .content("rich presence render *(should be cloned with an activity embed)*")?
+        .await?
+        .model()
+        .await?;
+    message.guild_id = Some(ctx.guild_id);
+    message.activity = Some(MessageActivity {
+        kind: MessageActivityType::Join,
+        party_id: Some("sparkle impostor party".to_owned()),
+    });
+    message.application = Some(MessageApplication {
+        cover_image: None,
+        description: String::new(),
+        icon: None,
+        id: Id::new(1),
+        name: "Sparkle Impostor".to_owned(),
+    });
+
+    assert!(matches!(
+        MessageSource::from_message(&message, &ctx.http),
+        Err(Error::RichPresence)
+    ));
+
+    MessageSource::from_message_with(
+        &message,
+        &ctx.http,
+        Options {
+            render_rich_presence: true,
+            ..Options::default()
+        },
+    )?
+    .create()
+    .await?;
+
+    Ok(())
+}
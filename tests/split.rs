@@ -0,0 +1,72 @@
+use common::Context;
+use sparkle_impostor::{constructor::Options, error::Error, MessageSource};
+use twilight_model::http::attachment::Attachment;
+use twilight_validate::message::MESSAGE_CONTENT_LENGTH_MAX;
+
+mod common;
+
+fn split_options() -> Options {
+    Options {
+        split_content: true,
+        ..Options::default()
+    }
+}
+
+#[tokio::test]
+async fn content() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .content("split content *(should be cloned as 3 messages, the code block in each)*")?
+        .await?
+        .model()
+        .await?;
+    message.guild_id = Some(ctx.guild_id);
+    message.content.push_str("\n```rust\n");
+    message.content.push_str(
+        &"let sparkle = \"impostor\";\n".repeat(MESSAGE_CONTENT_LENGTH_MAX.div_euclid(12)),
+    );
+    message.content.push_str("```");
+
+    assert!(matches!(
+        MessageSource::from_message(&message, &ctx.http),
+        Err(Error::ContentInvalid)
+    ));
+
+    MessageSource::from_message_with(&message, &ctx.http, split_options())?
+        .create()
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn attachment_link() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .create_message()
+        .content("split attachment link *(should be cloned as 2 messages, links in the last)*")?
+        .attachments(&[Attachment {
+            description: None,
+            file: vec![1],
+            filename: "split_attachment_link.txt".to_owned(),
+            id: 0,
+        }])?
+        .await?
+        .model()
+        .await?;
+    message.guild_id = Some(ctx.guild_id);
+    message.content.push('\n');
+    message
+        .content
+        .push_str(&"a".repeat(MESSAGE_CONTENT_LENGTH_MAX - message.content.chars().count()));
+
+    MessageSource::from_message_with(&message, &ctx.http, split_options())?
+        .handle_attachment_link()?
+        .create()
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use common::Context;
+use sparkle_impostor::{constructor::Options, error::Error, MessageSource};
+use twilight_model::channel::message::MessageType;
+
+mod common;
+
+#[tokio::test]
+async fn render() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let pinned_message = ctx
+        .create_message()
+        .content("system message render *(the pin message below should be cloned as text)*")?
+        .await?
+        .model()
+        .await?;
+
+    ctx.http
+        .create_pin(ctx.channel_id, pinned_message.id)
+        .await?;
+
+    let mut message = ctx
+        .http
+        .channel_messages(ctx.channel_id)
+        .limit(1)?
+        .await?
+        .models()
+        .await?
+        .remove(0);
+    message.guild_id = Some(ctx.guild_id);
+
+    assert_eq!(message.kind, MessageType::ChannelMessagePinned);
+    assert!(matches!(
+        MessageSource::from_message(&message, &ctx.http),
+        Err(Error::System)
+    ));
+
+    MessageSource::from_message_with(
+        &message,
+        &ctx.http,
+        Options {
+            render_system: true,
+            ..Options::default()
+        },
+    )?
+    .create()
+    .await?;
+
+    Ok(())
+}
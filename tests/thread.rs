@@ -1,5 +1,8 @@
 use common::Context;
+use sparkle_impostor::error::Error;
 use twilight_model::channel::ChannelType;
+#[cfg(feature = "upload")]
+use twilight_model::http::attachment::Attachment;
 
 mod common;
 
@@ -142,3 +145,160 @@ async fn forum_message() -> Result<(), anyhow::Error> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn media_post_no_attachment() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .http
+        .create_forum_thread(
+            ctx.forum_channel_id,
+            "sparkle impostor media post no attachment",
+        )
+        .message()
+        .content("forum post to media channel without attachment *(should not be cloned)*")?
+        .await?
+        .model()
+        .await?
+        .message;
+
+    let mut message_source = ctx.message_source(&mut message)?.handle_thread().await?;
+    message_source.channel_id = ctx.media_channel_id;
+
+    assert!(matches!(
+        message_source.handle_post_destination().await,
+        Err(Error::MediaPostAttachment)
+    ));
+
+    Ok(())
+}
+
+#[cfg(feature = "upload")]
+#[tokio::test]
+async fn media_post() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let mut message = ctx
+        .http
+        .create_forum_thread(ctx.forum_channel_id, "sparkle impostor media post")
+        .message()
+        .content("forum post to media channel *(should be cloned as a media post)*")?
+        .attachments(&[Attachment {
+            description: None,
+            file: vec![1],
+            filename: "media_post.txt".to_owned(),
+            id: 0,
+        }])?
+        .await?
+        .model()
+        .await?
+        .message;
+
+    let mut message_source = ctx
+        .message_source(&mut message)?
+        .handle_thread()
+        .await?
+        .handle_attachment_upload()
+        .await?;
+    message_source.channel_id = ctx.media_channel_id;
+
+    message_source
+        .handle_post_destination()
+        .await?
+        .create()
+        .await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn archived() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let thread = ctx
+        .http
+        .create_thread(
+            ctx.channel_id,
+            "sparkle impostor thread archived",
+            ChannelType::PublicThread,
+        )?
+        .await?
+        .model()
+        .await?;
+
+    let mut message = ctx
+        .http
+        .create_message(thread.id)
+        .content("message in archived thread *(should be cloned, thread should stay archived)*")?
+        .await?
+        .model()
+        .await?;
+
+    ctx.http.update_thread(thread.id).archived(true).await?;
+
+    let message_source = ctx
+        .message_source(&mut message)?
+        .handle_thread()
+        .await?
+        .handle_thread_archived()
+        .await?;
+
+    assert!(message_source.thread_archived.is_some());
+
+    message_source.create().await?;
+
+    assert!(
+        ctx.http
+            .channel(thread.id)
+            .await?
+            .model()
+            .await?
+            .thread_metadata
+            .unwrap()
+            .archived
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn private() -> Result<(), anyhow::Error> {
+    let ctx = Context::new().await;
+
+    let thread = ctx
+        .http
+        .create_thread(
+            ctx.channel_id,
+            "sparkle impostor private thread",
+            ChannelType::PrivateThread,
+        )?
+        .await?
+        .model()
+        .await?;
+
+    ctx.http
+        .add_thread_member(thread.id, ctx.owner.user.id)
+        .await?;
+
+    let mut message = ctx
+        .http
+        .create_message(thread.id)
+        .content(
+            "message in private thread *(should be cloned to another private thread with the \
+             same members)*",
+        )?
+        .await?
+        .model()
+        .await?;
+
+    ctx.message_source(&mut message)?
+        .handle_thread()
+        .await?
+        .handle_private_thread(true)
+        .await?
+        .create()
+        .await?;
+
+    Ok(())
+}